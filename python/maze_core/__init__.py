@@ -1,4 +1,4 @@
-from maze_core._core import run, parse_config, GameState, Action, AgentState, HitInfo, MazeConfig, AgentConfig, FlagConfig, CapturePointConfig, CameraConfig, OccupancyGrid, OccupancyGridEntry, EntityType, SensorConfidence
+from maze_core._core import run, parse_config, GameState, Action, AgentState, HitInfo, MazeConfig, AgentConfig, PolicyErrorMode, BudgetOverrunMode, BuiltinPolicy, FlagConfig, CapturePointConfig, CameraConfig, AgentView, ViewLayout, RewardConfig, OccupancyGrid, OccupancyGridEntry, EntityType, SensorConfidence, EvaluationSummary, LatencyStats, ActionResult, ActionStatus, MapMetadata, OccupancyGridView, CostMapConfig, PrivilegedInfo, FlagInfo, CapturePointInfo, FlagStatus, RecordingConfig, RecordingFormat, GridOverlay, ImageObservationConfig, ObservationFrame, DebugDraw
 
 from typing import Protocol, runtime_checkable
 
@@ -7,10 +7,37 @@ Velocity = tuple[float, float]
 
 @runtime_checkable
 class AgentProtocol(Protocol):
+    """The interface `run` expects from a policy.
+
+    `startup` and `on_episode_end` are optional: the simulator only calls them if the policy
+    defines them.
+
+    Before the first call the simulator also sets a `debug_draw` attribute, a `DebugDraw` for
+    drawing the policy's internals over the scene during `get_action`, unless the policy
+    already has one.
+
+    Next to `position`, a policy may set `particles`, rows of (x, y, weight), and a 2x2
+    `covariance`, as nested lists or NumPy arrays; `pose_particles` and `pose_covariance` are
+    accepted too. The window draws them around the estimated position and reports the
+    estimate's NEES against the true position at the time of the call; without a covariance,
+    the particles' is used. Values that cannot be read are ignored with a warning.
+    """
+
+    position: Position
+    """The policy's own estimate of the agent position, read after every `get_action` call."""
+
     def __init__(self) -> None: ...
 
-    def startup(self, initial_state: GameState) -> None: ...
+    def startup(self, initial_state: GameState, metadata: MapMetadata, config: MazeConfig) -> None:
+        """Called once with the first state, before the first `get_action`."""
+        ...
+
+    def get_action(self, state: GameState, grid: OccupancyGridView, elapsed: float) -> Action:
+        """Called at `AgentConfig.policy_hz`. `elapsed` is the seconds since the previous call returned."""
+        ...
 
-    def get_action(self, game_state: GameState) -> Action: ...
+    def on_episode_end(self, summary: EvaluationSummary) -> None:
+        """Called once when the simulation stops."""
+        ...
 
-__all__ = ["run", "parse_config", "GameState", "Action", "AgentState", "HitInfo", "AgentProtocol", "MazeConfig", "AgentConfig", "FlagConfig", "CapturePointConfig", "CameraConfig", "OccupancyGrid", "OccupancyGridEntry", "EntityType", "Position", "Velocity", "SensorConfidence"]
\ No newline at end of file
+__all__ = ["run", "parse_config", "GameState", "Action", "AgentState", "HitInfo", "AgentProtocol", "MazeConfig", "AgentConfig", "PolicyErrorMode", "BudgetOverrunMode", "BuiltinPolicy", "FlagConfig", "CapturePointConfig", "CameraConfig", "AgentView", "ViewLayout", "RewardConfig", "OccupancyGrid", "OccupancyGridEntry", "EntityType", "Position", "Velocity", "SensorConfidence", "EvaluationSummary", "LatencyStats", "ActionResult", "ActionStatus", "MapMetadata", "OccupancyGridView", "CostMapConfig", "PrivilegedInfo", "FlagInfo", "CapturePointInfo", "FlagStatus", "RecordingConfig", "RecordingFormat", "GridOverlay", "ImageObservationConfig", "ObservationFrame", "DebugDraw"]
\ No newline at end of file
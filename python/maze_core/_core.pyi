@@ -2,6 +2,8 @@
 # ruff: noqa: E501, F401
 
 import builtins
+import numpy
+import numpy.typing
 import typing
 from enum import Enum
 
@@ -28,6 +30,24 @@ class Action:
     
     ...
 
+class ActionResult:
+    @property
+    def action(self) -> Action:
+        r"""
+        The action that was submitted by the policy.
+        """
+    @property
+    def status(self) -> ActionStatus:
+        r"""
+        How the simulation handled the action.
+        """
+    @property
+    def reason(self) -> typing.Optional[builtins.str]:
+        r"""
+        Why the action was clamped or rejected. None if it was accepted.
+        """
+    def __str__(self) -> builtins.str: ...
+
 class AgentConfig:
     @property
     def name(self) -> builtins.str: ...
@@ -53,6 +73,60 @@ class AgentConfig:
     def occupancy_grid_cell_size(self) -> builtins.float: ...
     @occupancy_grid_cell_size.setter
     def occupancy_grid_cell_size(self, value: builtins.float) -> None: ...
+    @property
+    def on_policy_error(self) -> PolicyErrorMode: ...
+    @on_policy_error.setter
+    def on_policy_error(self, value: PolicyErrorMode) -> None: ...
+    @property
+    def policy_budget_ms(self) -> typing.Optional[builtins.float]:
+        r"""
+        How long a single `get_action` call may take, in milliseconds. None means no limit.
+        """
+    @policy_budget_ms.setter
+    def policy_budget_ms(self, value: typing.Optional[builtins.float]) -> None:
+        r"""
+        How long a single `get_action` call may take, in milliseconds. None means no limit.
+        """
+    @property
+    def on_budget_overrun(self) -> BudgetOverrunMode: ...
+    @on_budget_overrun.setter
+    def on_budget_overrun(self, value: BudgetOverrunMode) -> None: ...
+    @property
+    def policy_server(self) -> typing.Optional[builtins.str]:
+        r"""
+        Address of an out-of-process policy server, either `unix:/path/to/socket` or a TCP
+        address such as `127.0.0.1:5555`. When set, the policy passed to `run` is not used.
+        """
+    @policy_server.setter
+    def policy_server(self, value: typing.Optional[builtins.str]) -> None:
+        r"""
+        Address of an out-of-process policy server, either `unix:/path/to/socket` or a TCP
+        address such as `127.0.0.1:5555`. When set, the policy passed to `run` is not used.
+        """
+    @property
+    def builtin_policy(self) -> BuiltinPolicy:
+        r"""
+        The Rust policy that drives the agent when neither a Python policy nor `policy_server`
+        is given.
+        """
+    @builtin_policy.setter
+    def builtin_policy(self, value: BuiltinPolicy) -> None:
+        r"""
+        The Rust policy that drives the agent when neither a Python policy nor `policy_server`
+        is given.
+        """
+    @property
+    def demonstration_path(self) -> typing.Optional[builtins.str]:
+        r"""
+        With the teleop policy, a JSON Lines file to write every state and the action taken in it
+        to, as a demonstration for imitation learning. Nothing is written when None.
+        """
+    @demonstration_path.setter
+    def demonstration_path(self, value: typing.Optional[builtins.str]) -> None:
+        r"""
+        With the teleop policy, a JSON Lines file to write every state and the action taken in it
+        to, as a demonstration for imitation learning. Nothing is written when None.
+        """
 
 class AgentState:
     @property
@@ -85,12 +159,149 @@ class AgentState:
         r"""
         The maximum linear speed of the agent.
         """
+    @property
+    def colliding(self) -> builtins.bool:
+        r"""
+        Whether the agent is currently touching a wall.
+        """
+    @property
+    def contact_normals(self) -> builtins.list[tuple[builtins.float, builtins.float]]:
+        r"""
+        The normals of the wall contacts in world coordinates, pointing from the wall towards the agent.
+        """
+    @property
+    def impacts(self) -> builtins.int:
+        r"""
+        How many times the agent ran into a wall since the previous state.
+        """
 
 class CameraConfig:
     @property
-    def scale(self) -> builtins.float: ...
+    def zoom(self) -> builtins.float:
+        r"""
+        Zoom relative to fitting the whole maze in the window: 0.5 shows half as much. The sign
+        sets the orientation of the view.
+        """
+    @zoom.setter
+    def zoom(self, value: builtins.float) -> None:
+        r"""
+        Zoom relative to fitting the whole maze in the window: 0.5 shows half as much. The sign
+        sets the orientation of the view.
+        """
+    @property
+    def scale(self) -> typing.Optional[builtins.float]:
+        r"""
+        Deprecated in favor of `zoom`. World units per window pixel, signed like `zoom`. When
+        set, the view starts out at the zoom showing that much of the maze.
+        """
     @scale.setter
-    def scale(self, value: builtins.float) -> None: ...
+    def scale(self, value: typing.Optional[builtins.float]) -> None:
+        r"""
+        Deprecated in favor of `zoom`. World units per window pixel, signed like `zoom`. When
+        set, the view starts out at the zoom showing that much of the maze.
+        """
+    @property
+    def min_zoom(self) -> builtins.float:
+        r"""
+        The closest and farthest zoom allowed, as magnitudes of `zoom`.
+        """
+    @min_zoom.setter
+    def min_zoom(self, value: builtins.float) -> None:
+        r"""
+        The closest and farthest zoom allowed, as magnitudes of `zoom`.
+        """
+    @property
+    def max_zoom(self) -> builtins.float: ...
+    @max_zoom.setter
+    def max_zoom(self, value: builtins.float) -> None: ...
+    @property
+    def pan_speed(self) -> builtins.float:
+        r"""
+        How fast the arrow keys pan, in window heights per second.
+        """
+    @pan_speed.setter
+    def pan_speed(self, value: builtins.float) -> None:
+        r"""
+        How fast the arrow keys pan, in window heights per second.
+        """
+    @property
+    def zoom_speed(self) -> builtins.float:
+        r"""
+        How much holding + or - zooms per second, as a factor.
+        """
+    @zoom_speed.setter
+    def zoom_speed(self, value: builtins.float) -> None:
+        r"""
+        How much holding + or - zooms per second, as a factor.
+        """
+    @property
+    def scroll_zoom(self) -> builtins.float:
+        r"""
+        How much one notch of the scroll wheel zooms, as a factor.
+        """
+    @scroll_zoom.setter
+    def scroll_zoom(self, value: builtins.float) -> None:
+        r"""
+        How much one notch of the scroll wheel zooms, as a factor.
+        """
+    @property
+    def smoothing(self) -> builtins.float:
+        r"""
+        How quickly the camera catches up with where it is asked to be, per second. Zero moves
+        it there at once.
+        """
+    @smoothing.setter
+    def smoothing(self, value: builtins.float) -> None:
+        r"""
+        How quickly the camera catches up with where it is asked to be, per second. Zero moves
+        it there at once.
+        """
+    @property
+    def follow_agent(self) -> builtins.bool:
+        r"""
+        Start out following the agent, which F toggles.
+        """
+    @follow_agent.setter
+    def follow_agent(self, value: builtins.bool) -> None:
+        r"""
+        Start out following the agent, which F toggles.
+        """
+    @property
+    def agent_view(self) -> AgentView:
+        r"""
+        What the second view, centered on the agent, shows at the start. V cycles through the
+        options.
+        """
+    @agent_view.setter
+    def agent_view(self, value: AgentView) -> None:
+        r"""
+        What the second view, centered on the agent, shows at the start. V cycles through the
+        options.
+        """
+    @property
+    def agent_view_layout(self) -> ViewLayout: ...
+    @agent_view_layout.setter
+    def agent_view_layout(self, value: ViewLayout) -> None: ...
+    @property
+    def agent_view_size(self) -> builtins.float:
+        r"""
+        The width and height of the picture-in-picture view, as a fraction of the window's.
+        """
+    @agent_view_size.setter
+    def agent_view_size(self, value: builtins.float) -> None:
+        r"""
+        The width and height of the picture-in-picture view, as a fraction of the window's.
+        """
+    @property
+    def agent_view_extent(self) -> builtins.float:
+        r"""
+        How much of the maze the agent view spans vertically, in world units.
+        """
+    @agent_view_extent.setter
+    def agent_view_extent(self, value: builtins.float) -> None:
+        r"""
+        How much of the maze the agent view spans vertically, in world units.
+        """
 
 class CapturePointConfig:
     @property
@@ -98,12 +309,179 @@ class CapturePointConfig:
     @number.setter
     def number(self, value: builtins.int) -> None: ...
 
+class CapturePointInfo:
+    @property
+    def id(self) -> builtins.int:
+        r"""
+        The entity ID of the capture point.
+        """
+    @property
+    def position(self) -> tuple[builtins.float, builtins.float]:
+        r"""
+        The position of the capture point in world coordinates.
+        """
+    def __str__(self) -> builtins.str: ...
+
+class CostMapConfig:
+    r"""
+    How the planners on `OccupancyGrid` weigh cells. Distances are in world units.
+    """
+    @property
+    def wall_threshold(self) -> builtins.float:
+        r"""
+        Cells whose wall probability is at least this are impassable, as are cells assigned to
+        walls.
+        """
+    @wall_threshold.setter
+    def wall_threshold(self, value: builtins.float) -> None:
+        r"""
+        Cells whose wall probability is at least this are impassable, as are cells assigned to
+        walls.
+        """
+    @property
+    def inflation_radius(self) -> builtins.float:
+        r"""
+        Cells whose center is closer than this to an impassable cell cost extra to enter.
+        """
+    @inflation_radius.setter
+    def inflation_radius(self, value: builtins.float) -> None:
+        r"""
+        Cells whose center is closer than this to an impassable cell cost extra to enter.
+        """
+    @property
+    def inflation_cost(self) -> builtins.float:
+        r"""
+        The extra cost of a cell right next to an impassable cell, falling off linearly to zero
+        at `inflation_radius`.
+        """
+    @inflation_cost.setter
+    def inflation_cost(self, value: builtins.float) -> None:
+        r"""
+        The extra cost of a cell right next to an impassable cell, falling off linearly to zero
+        at `inflation_radius`.
+        """
+    @property
+    def unknown_cost(self) -> builtins.float:
+        r"""
+        The extra cost of entering a cell with no assignment.
+        """
+    @unknown_cost.setter
+    def unknown_cost(self, value: builtins.float) -> None:
+        r"""
+        The extra cost of entering a cell with no assignment.
+        """
+    def __new__(cls, wall_threshold:builtins.float=0.5, inflation_radius:builtins.float=1.0, inflation_cost:builtins.float=5.0, unknown_cost:builtins.float=0.0) -> CostMapConfig: ...
+    def __repr__(self) -> builtins.str: ...
+    def __str__(self) -> builtins.str: ...
+
+class DebugDraw:
+    r"""
+    Queues shapes to draw over the scene, in world coordinates. Colors are (r, g, b) or
+    (r, g, b, a) tuples of floats between 0 and 1, yellow by default.
+    """
+    def line(self, start:tuple[builtins.float, builtins.float], end:tuple[builtins.float, builtins.float], color:typing.Optional[typing.Sequence[builtins.float]]=None) -> None: ...
+    def path(self, points:typing.Sequence[tuple[builtins.float, builtins.float]], color:typing.Optional[typing.Sequence[builtins.float]]=None) -> None:
+        r"""
+        Lines joining consecutive points, such as a planned path.
+        """
+    def point(self, position:tuple[builtins.float, builtins.float], color:typing.Optional[typing.Sequence[builtins.float]]=None, size:builtins.float=0.30000001192092896) -> None:
+        r"""
+        A filled dot `size` world units across.
+        """
+    def points(self, positions:typing.Sequence[tuple[builtins.float, builtins.float]], color:typing.Optional[typing.Sequence[builtins.float]]=None, size:builtins.float=0.30000001192092896) -> None:
+        r"""
+        Draws many points at once, such as particles.
+        """
+    def circle(self, center:tuple[builtins.float, builtins.float], radius:builtins.float, color:typing.Optional[typing.Sequence[builtins.float]]=None) -> None:
+        r"""
+        The outline of a circle.
+        """
+    def text(self, position:tuple[builtins.float, builtins.float], text:builtins.str, color:typing.Optional[typing.Sequence[builtins.float]]=None) -> None:
+        r"""
+        A text label anchored at `position`. Labels are only shown in the window.
+        """
+    def heatmap(self, values:typing.Sequence[typing.Sequence[builtins.float]], min:typing.Optional[builtins.float]=None, max:typing.Optional[builtins.float]=None, alpha:builtins.float=0.5) -> None:
+        r"""
+        Colors every cell of the player grid by a value, indexed [x][y] like
+        `OccupancyGrid.cost_map`. Values are scaled from `min` (blue) to `max` (red), which
+        default to the smallest and largest finite values. NaN and infinite cells are left clear.
+        """
+    def clear(self) -> None:
+        r"""
+        Drops everything drawn so far in this call.
+        """
+
+class EvaluationSummary:
+    r"""
+    End-of-run statistics used to grade a policy.
+    """
+    @property
+    def elapsed(self) -> builtins.float:
+        r"""
+        Seconds of simulated time.
+        """
+    @property
+    def total_flags(self) -> builtins.int: ...
+    @property
+    def collected_flags(self) -> builtins.int: ...
+    @property
+    def cumulative_reward(self) -> builtins.float:
+        r"""
+        The sum of all rewards handed to the policy.
+        """
+    @property
+    def total_collisions(self) -> builtins.int:
+        r"""
+        How many times the agent ran into a wall.
+        """
+    @property
+    def mapping_error(self) -> builtins.float:
+        r"""
+        Fraction of wall and free cells that the player grid got wrong.
+        """
+    @property
+    def explored_fraction(self) -> builtins.float:
+        r"""
+        Fraction of the cells reachable from the spawn point that the player grid has assigned.
+        """
+    @property
+    def coverage(self) -> builtins.list[tuple[builtins.float, builtins.float]]:
+        r"""
+        The explored fraction sampled about once per second, as (elapsed seconds, fraction).
+        """
+    @property
+    def policy_latency(self) -> LatencyStats:
+        r"""
+        How long the policy took to compute its actions.
+        """
+    @property
+    def disqualified(self) -> builtins.bool:
+        r"""
+        Whether the run was ended early because the policy went over its time budget.
+        """
+    def __str__(self) -> builtins.str: ...
+
 class FlagConfig:
     @property
     def number(self) -> builtins.int: ...
     @number.setter
     def number(self, value: builtins.int) -> None: ...
 
+class FlagInfo:
+    @property
+    def id(self) -> builtins.int:
+        r"""
+        The entity ID of the flag, as in `AgentState.flag` and the `PickupFlag` action.
+        """
+    @property
+    def status(self) -> FlagStatus: ...
+    @property
+    def position(self) -> tuple[builtins.float, builtins.float]:
+        r"""
+        The position of the flag in world coordinates.
+        """
+    def __str__(self) -> builtins.str: ...
+
 class GameState:
     @property
     def agent(self) -> AgentState: ...
@@ -112,9 +490,64 @@ class GameState:
     @property
     def collected_flags(self) -> builtins.int: ...
     @property
-    def world_width(self) -> builtins.float: ...
+    def remaining_flags(self) -> builtins.int:
+        r"""
+        Flags that have not been captured yet, `total_flags - collected_flags`.
+        """
+    @property
+    def flags(self) -> builtins.list[FlagInfo]:
+        r"""
+        The flags the agent has seen or carried so far, ordered by id, with their status and
+        position when last observed. Positions are where the agent placed them, from its noisy
+        pose and range readings.
+        """
+    @property
+    def capture_points(self) -> builtins.list[CapturePointInfo]:
+        r"""
+        The capture points the agent has seen so far, ordered by id, placed like `flags`.
+        """
+    @property
+    def world_width(self) -> builtins.float:
+        r"""
+        Size of the world along x, in world units. Same as `metadata.world_width`.
+        """
+    @property
+    def world_height(self) -> builtins.float:
+        r"""
+        Size of the world along y, in world units. Same as `metadata.world_height`.
+        """
+    @property
+    def metadata(self) -> MapMetadata:
+        r"""
+        Fixed facts about the map: its size, origin and cell sizes.
+        """
+    @property
+    def reward(self) -> builtins.float:
+        r"""
+        The shaped reward earned since the previous state, see `RewardConfig`.
+        """
+    @property
+    def cumulative_reward(self) -> builtins.float:
+        r"""
+        The sum of all rewards earned so far this episode.
+        """
+    @property
+    def action_result(self) -> typing.Optional[ActionResult]:
+        r"""
+        The outcome of the last action applied since the previous state, if any.
+        """
+    @property
+    def privileged_info(self) -> typing.Optional[PrivilegedInfo]:
+        r"""
+        Ground truth about the agent and the map. None unless `MazeConfig.privileged_info` is set.
+        """
     @property
-    def world_height(self) -> builtins.float: ...
+    def image(self) -> typing.Optional[numpy.typing.NDArray[numpy.uint8]]:
+        r"""
+        A top-down picture of the map around the agent as a (height, width, 4) RGBA uint8
+        array, in the same colors as the grid overlay in the window. None unless
+        `MazeConfig.image_observation.enabled` is set.
+        """
 
 class HitInfo:
     @property
@@ -150,6 +583,141 @@ class HitInfo:
         """
     def __str__(self) -> builtins.str: ...
 
+class ImageObservationConfig:
+    r"""
+    A top-down image of an occupancy grid added to every `GameState`, for convolutional policies.
+    """
+    @property
+    def enabled(self) -> builtins.bool:
+        r"""
+        Fill in `GameState.image`.
+        """
+    @enabled.setter
+    def enabled(self, value: builtins.bool) -> None:
+        r"""
+        Fill in `GameState.image`.
+        """
+    @property
+    def width(self) -> builtins.int:
+        r"""
+        Image width in pixels.
+        """
+    @width.setter
+    def width(self, value: builtins.int) -> None:
+        r"""
+        Image width in pixels.
+        """
+    @property
+    def height(self) -> builtins.int:
+        r"""
+        Image height in pixels.
+        """
+    @height.setter
+    def height(self, value: builtins.int) -> None:
+        r"""
+        Image height in pixels.
+        """
+    @property
+    def frame(self) -> ObservationFrame: ...
+    @frame.setter
+    def frame(self, value: ObservationFrame) -> None: ...
+    @property
+    def view_size(self) -> builtins.float:
+        r"""
+        With an egocentric frame, how many world units the image spans across its width.
+        """
+    @view_size.setter
+    def view_size(self, value: builtins.float) -> None:
+        r"""
+        With an egocentric frame, how many world units the image spans across its width.
+        """
+    @property
+    def grid(self) -> GridOverlay:
+        r"""
+        The grid to draw. With the player grid, markers show the estimated agent position and
+        the flags and capture points in `GameState.flags` and `GameState.capture_points`. With
+        the true grid, they show the true positions of everything.
+        """
+    @grid.setter
+    def grid(self, value: GridOverlay) -> None:
+        r"""
+        The grid to draw. With the player grid, markers show the estimated agent position and
+        the flags and capture points in `GameState.flags` and `GameState.capture_points`. With
+        the true grid, they show the true positions of everything.
+        """
+
+class LatencyStats:
+    r"""
+    Statistics about `get_action` call durations.
+    """
+    @property
+    def calls(self) -> builtins.int:
+        r"""
+        Number of times the policy was called.
+        """
+    @property
+    def mean_ms(self) -> builtins.float: ...
+    @property
+    def p50_ms(self) -> builtins.float: ...
+    @property
+    def p90_ms(self) -> builtins.float: ...
+    @property
+    def p99_ms(self) -> builtins.float: ...
+    @property
+    def max_ms(self) -> builtins.float: ...
+    @property
+    def overruns(self) -> builtins.int:
+        r"""
+        Number of calls that took longer than `policy_budget_ms`.
+        """
+    @property
+    def missed_ticks(self) -> builtins.int:
+        r"""
+        Number of policy ticks whose state was dropped because the policy was still busy.
+        """
+    def __str__(self) -> builtins.str: ...
+
+class MapMetadata:
+    r"""
+    Fixed facts about the map, handed to the policy's `startup` hook.
+    """
+    @property
+    def world_width(self) -> builtins.float:
+        r"""
+        Size of the world along x, in world units. The world is centered on the origin.
+        """
+    @property
+    def world_height(self) -> builtins.float:
+        r"""
+        Size of the world along z (y in `GameState` coordinates), in world units.
+        """
+    @property
+    def maze_cell_size(self) -> builtins.float:
+        r"""
+        Width of the maze corridors, in world units.
+        """
+    @property
+    def grid_cell_size(self) -> builtins.float:
+        r"""
+        Size of each occupancy grid cell, in world units.
+        """
+    @property
+    def grid_width(self) -> builtins.int:
+        r"""
+        Number of occupancy grid cells in the x direction.
+        """
+    @property
+    def grid_height(self) -> builtins.int:
+        r"""
+        Number of occupancy grid cells in the y direction.
+        """
+    @property
+    def origin(self) -> tuple[builtins.float, builtins.float]:
+        r"""
+        World coordinates of the corner of occupancy grid cell (0, 0).
+        """
+    def __str__(self) -> builtins.str: ...
+
 class MazeConfig:
     @property
     def agent(self) -> AgentConfig: ...
@@ -172,6 +740,18 @@ class MazeConfig:
     @maze_generation.setter
     def maze_generation(self, value: MazeGenerationConfig) -> None: ...
     @property
+    def reward(self) -> RewardConfig: ...
+    @reward.setter
+    def reward(self, value: RewardConfig) -> None: ...
+    @property
+    def recording(self) -> RecordingConfig: ...
+    @recording.setter
+    def recording(self, value: RecordingConfig) -> None: ...
+    @property
+    def image_observation(self) -> ImageObservationConfig: ...
+    @image_observation.setter
+    def image_observation(self, value: ImageObservationConfig) -> None: ...
+    @property
     def debug(self) -> builtins.bool: ...
     @debug.setter
     def debug(self, value: builtins.bool) -> None: ...
@@ -183,6 +763,18 @@ class MazeConfig:
     def use_true_map(self) -> builtins.bool: ...
     @use_true_map.setter
     def use_true_map(self, value: builtins.bool) -> None: ...
+    @property
+    def privileged_info(self) -> builtins.bool:
+        r"""
+        Fill in `GameState.privileged_info` with ground truth the agent could not sense, for
+        debugging and privileged training. Policies under evaluation should not rely on it.
+        """
+    @privileged_info.setter
+    def privileged_info(self, value: builtins.bool) -> None:
+        r"""
+        Fill in `GameState.privileged_info` with ground truth the agent could not sense, for
+        debugging and privileged training. Policies under evaluation should not rely on it.
+        """
 
 class MazeGenerationConfig:
     @property
@@ -201,6 +793,20 @@ class MazeGenerationConfig:
     def cell_size(self) -> builtins.float: ...
     @cell_size.setter
     def cell_size(self, value: builtins.float) -> None: ...
+    @property
+    def layout(self) -> typing.Optional[builtins.str]:
+        r"""
+        A YAML layout file saved from the editor, used instead of a generated maze. Its size
+        replaces `width` and `height`, and its flags and capture points replace the configured
+        numbers.
+        """
+    @layout.setter
+    def layout(self, value: typing.Optional[builtins.str]) -> None:
+        r"""
+        A YAML layout file saved from the editor, used instead of a generated maze. Its size
+        replaces `width` and `height`, and its flags and capture points replace the configured
+        numbers.
+        """
 
 class OccupancyGrid:
     @property
@@ -219,12 +825,65 @@ class OccupancyGrid:
         Number of cells in the y direction
         """
     @property
+    def origin(self) -> tuple[builtins.float, builtins.float]:
+        r"""
+        World (x, y) coordinates of the corner of cell (0, 0). Column indexes grow along +x and
+        row indexes along +y, which points down on the screen.
+        """
+    @property
     def shape(self) -> tuple[builtins.int, builtins.int]:
         r"""
         Returns (width, height)
         """
-    def __new__(cls, width:builtins.int, height:builtins.int, cell_size:builtins.float) -> OccupancyGrid: ...
+    def __new__(cls, width:builtins.int, height:builtins.int, cell_size:builtins.float, origin:typing.Optional[tuple[builtins.float, builtins.float]]=None) -> OccupancyGrid:
+        r"""
+        Creates an empty grid. Without an `origin`, the grid is centered on the world origin.
+        """
     def __getitem__(self, key:typing.Any) -> OccupancyGridEntry: ...
+    def world_to_cell(self, position:tuple[builtins.float, builtins.float]) -> typing.Optional[tuple[builtins.int, builtins.int]]:
+        r"""
+        The (x, y) index of the cell containing a world (x, y) position, or None if it is outside
+        the grid. The world y coordinate is the z axis of the 3D scene, as in `GameState`.
+        """
+    def cell_to_world(self, cell:tuple[builtins.int, builtins.int]) -> tuple[builtins.float, builtins.float]:
+        r"""
+        The world (x, y) position of the center of a cell.
+        """
+    def cells_in_aabb(self, min:tuple[builtins.float, builtins.float], max:tuple[builtins.float, builtins.float]) -> builtins.list[tuple[builtins.int, builtins.int]]:
+        r"""
+        The cells overlapping the axis-aligned box between the world positions `min` and `max`,
+        row by row. Cells the box only touches along an edge are left out.
+        """
+    def ray_cells(self, start:tuple[builtins.float, builtins.float], end:tuple[builtins.float, builtins.float]) -> builtins.list[tuple[builtins.int, builtins.int]]:
+        r"""
+        The cells a straight line from `start` to `end` passes through, in order, including the
+        cells containing both ends. The parts of the line outside the grid are skipped.
+        """
+    def frontier_cells(self) -> builtins.list[tuple[builtins.int, builtins.int]]:
+        r"""
+        Known free cells that share an edge with an unassigned cell, as (x, y) indexes.
+        """
+    def frontier_clusters(self) -> builtins.list[builtins.list[tuple[builtins.int, builtins.int]]]:
+        r"""
+        The frontier cells grouped into clusters that touch, including diagonally.
+        """
+    def cost_map(self, config:typing.Optional[CostMapConfig]=None) -> builtins.list[builtins.list[builtins.float]]:
+        r"""
+        The extra cost of entering each cell, indexed as `costs[x][y]`. Impassable cells are
+        `inf`. Uses the default `CostMapConfig` if none is given.
+        """
+    def find_path(self, start:tuple[builtins.float, builtins.float], goal:tuple[builtins.float, builtins.float], config:typing.Optional[CostMapConfig]=None) -> typing.Optional[builtins.list[tuple[builtins.float, builtins.float]]]:
+        r"""
+        Finds the cheapest path from `start` to `goal` with A*, as the world positions of the
+        centers of the cells along it, from the start cell to the goal cell. Returns None if the
+        goal cannot be reached or either end is outside the grid.
+        """
+    def distance_transform(self, goals:typing.Sequence[tuple[builtins.float, builtins.float]], config:typing.Optional[CostMapConfig]=None) -> builtins.list[builtins.list[builtins.float]]:
+        r"""
+        The cheapest cost of getting from each cell to the closest of `goals`, indexed as
+        `distances[x][y]`, with Dijkstra's algorithm. Moving costs the distance travelled plus the
+        cost map entry of every cell entered. Unreachable cells are `inf`.
+        """
 
 class OccupancyGridEntry:
     @property
@@ -277,7 +936,176 @@ class OccupancyGridView:
     def height(self) -> builtins.int: ...
     @property
     def shape(self) -> tuple[builtins.int, builtins.int]: ...
+    @property
+    def origin(self) -> tuple[builtins.float, builtins.float]: ...
     def __getitem__(self, key:typing.Any) -> OccupancyGridEntry: ...
+    def world_to_cell(self, position:tuple[builtins.float, builtins.float]) -> typing.Optional[tuple[builtins.int, builtins.int]]:
+        r"""
+        See `OccupancyGrid.world_to_cell`.
+        """
+    def cell_to_world(self, cell:tuple[builtins.int, builtins.int]) -> tuple[builtins.float, builtins.float]:
+        r"""
+        See `OccupancyGrid.cell_to_world`.
+        """
+    def cells_in_aabb(self, min:tuple[builtins.float, builtins.float], max:tuple[builtins.float, builtins.float]) -> builtins.list[tuple[builtins.int, builtins.int]]:
+        r"""
+        See `OccupancyGrid.cells_in_aabb`.
+        """
+    def ray_cells(self, start:tuple[builtins.float, builtins.float], end:tuple[builtins.float, builtins.float]) -> builtins.list[tuple[builtins.int, builtins.int]]:
+        r"""
+        See `OccupancyGrid.ray_cells`.
+        """
+    def frontier_cells(self) -> builtins.list[tuple[builtins.int, builtins.int]]:
+        r"""
+        See `OccupancyGrid.frontier_cells`.
+        """
+    def frontier_clusters(self) -> builtins.list[builtins.list[tuple[builtins.int, builtins.int]]]:
+        r"""
+        See `OccupancyGrid.frontier_clusters`.
+        """
+    def cost_map(self, config:typing.Optional[CostMapConfig]=None) -> builtins.list[builtins.list[builtins.float]]:
+        r"""
+        See `OccupancyGrid.cost_map`.
+        """
+    def find_path(self, start:tuple[builtins.float, builtins.float], goal:tuple[builtins.float, builtins.float], config:typing.Optional[CostMapConfig]=None) -> typing.Optional[builtins.list[tuple[builtins.float, builtins.float]]]:
+        r"""
+        See `OccupancyGrid.find_path`.
+        """
+    def distance_transform(self, goals:typing.Sequence[tuple[builtins.float, builtins.float]], config:typing.Optional[CostMapConfig]=None) -> builtins.list[builtins.list[builtins.float]]:
+        r"""
+        See `OccupancyGrid.distance_transform`.
+        """
+
+class PrivilegedInfo:
+    r"""
+    Ground truth that the agent's sensors do not give it, see `MazeConfig.privileged_info`.
+    """
+    @property
+    def agent(self) -> AgentState:
+        r"""
+        The agent's state without noise: its true position and the true ray distances.
+        """
+    @property
+    def flags(self) -> builtins.list[FlagInfo]:
+        r"""
+        Every flag in the map, including carried and captured ones.
+        """
+    @property
+    def capture_points(self) -> builtins.list[CapturePointInfo]:
+        r"""
+        Every capture point in the map.
+        """
+
+class RecordingConfig:
+    r"""
+    Records top-down frames of the run with a CPU rasterizer, so it also works in headless mode.
+    """
+    @property
+    def output_dir(self) -> typing.Optional[builtins.str]:
+        r"""
+        Directory to write frames into, created if missing. Nothing is recorded when None.
+        """
+    @output_dir.setter
+    def output_dir(self, value: typing.Optional[builtins.str]) -> None:
+        r"""
+        Directory to write frames into, created if missing. Nothing is recorded when None.
+        """
+    @property
+    def format(self) -> RecordingFormat: ...
+    @format.setter
+    def format(self, value: RecordingFormat) -> None: ...
+    @property
+    def fps(self) -> builtins.float:
+        r"""
+        Frames per second of simulated time.
+        """
+    @fps.setter
+    def fps(self, value: builtins.float) -> None:
+        r"""
+        Frames per second of simulated time.
+        """
+    @property
+    def pixels_per_unit(self) -> builtins.float:
+        r"""
+        Image pixels per world unit.
+        """
+    @pixels_per_unit.setter
+    def pixels_per_unit(self, value: builtins.float) -> None:
+        r"""
+        Image pixels per world unit.
+        """
+    @property
+    def grid_overlay(self) -> GridOverlay:
+        r"""
+        Which occupancy grid to draw under the walls.
+        """
+    @grid_overlay.setter
+    def grid_overlay(self, value: GridOverlay) -> None:
+        r"""
+        Which occupancy grid to draw under the walls.
+        """
+
+class RewardConfig:
+    @property
+    def capture_bonus(self) -> builtins.float:
+        r"""
+        Reward for each flag delivered to a capture point.
+        """
+    @capture_bonus.setter
+    def capture_bonus(self, value: builtins.float) -> None:
+        r"""
+        Reward for each flag delivered to a capture point.
+        """
+    @property
+    def pickup_bonus(self) -> builtins.float:
+        r"""
+        Reward for the first time each flag is picked up.
+        """
+    @pickup_bonus.setter
+    def pickup_bonus(self, value: builtins.float) -> None:
+        r"""
+        Reward for the first time each flag is picked up.
+        """
+    @property
+    def time_penalty(self) -> builtins.float:
+        r"""
+        Penalty per second of simulated time.
+        """
+    @time_penalty.setter
+    def time_penalty(self, value: builtins.float) -> None:
+        r"""
+        Penalty per second of simulated time.
+        """
+    @property
+    def collision_penalty(self) -> builtins.float:
+        r"""
+        Penalty each time the agent runs into a wall.
+        """
+    @collision_penalty.setter
+    def collision_penalty(self, value: builtins.float) -> None:
+        r"""
+        Penalty each time the agent runs into a wall.
+        """
+    @property
+    def mapping_gain(self) -> builtins.float:
+        r"""
+        Reward per unit of mapping accuracy (0 to 1) gained against the true grid.
+        """
+    @mapping_gain.setter
+    def mapping_gain(self, value: builtins.float) -> None:
+        r"""
+        Reward per unit of mapping accuracy (0 to 1) gained against the true grid.
+        """
+    @property
+    def exploration_bonus(self) -> builtins.float:
+        r"""
+        Reward for each occupancy grid cell the agent visits for the first time.
+        """
+    @exploration_bonus.setter
+    def exploration_bonus(self, value: builtins.float) -> None:
+        r"""
+        Reward for each occupancy grid cell the agent visits for the first time.
+        """
 
 class SensorConfidence:
     @property
@@ -309,6 +1137,7 @@ class StateQueue:
     def get(self, timeout_ms:typing.Optional[builtins.int]) -> typing.Optional[tuple[GameState, OccupancyGridView, OccupancyGridView]]:
         r"""
         Wait for next GameState (timeout ms optional). Returns None on timeout.
+        Raises the policy's exception if it stopped the simulation.
         """
     def stop(self) -> None:
         r"""
@@ -318,6 +1147,86 @@ class StateQueue:
         r"""
         Join the sim thread.
         """
+    def summary(self) -> typing.Optional[EvaluationSummary]:
+        r"""
+        The evaluation summary of the run. Returns None until the sim has stopped.
+        """
+
+class ActionStatus(Enum):
+    r"""
+    Whether an action was applied as requested, applied after being adjusted, or not applied at all.
+    """
+    Accepted = ...
+    Clamped = ...
+    Rejected = ...
+
+class AgentView(Enum):
+    r"""
+    What the view that follows the agent shows.
+    """
+    Off = ...
+    r"""
+    No agent view.
+    """
+    Scene = ...
+    r"""
+    The true scene around the agent.
+    """
+    Belief = ...
+    r"""
+    Only the player grid and the estimated pose, which is what the agent believes.
+    """
+
+class BudgetOverrunMode(Enum):
+    r"""
+    What to do with an action that took longer than `policy_budget_ms` to compute.
+    """
+    Skip = ...
+    r"""
+    Discard the late action.
+    """
+    HoldLast = ...
+    r"""
+    Discard the late action and repeat the last action that was on time.
+    """
+    ZeroVelocity = ...
+    r"""
+    Discard the late action and stop the agent.
+    """
+    Disqualify = ...
+    r"""
+    End the run and mark the policy as disqualified.
+    """
+
+class BuiltinPolicy(Enum):
+    r"""
+    Reference policies implemented in Rust, used as baselines.
+    """
+    Scripted = ...
+    r"""
+    Heads for flags and capture points its rays can see, turning away from walls otherwise.
+    """
+    RandomWalk = ...
+    r"""
+    Drives in a random direction for a random while, turning early if a wall gets close.
+    """
+    WallFollower = ...
+    r"""
+    Keeps a wall on its right hand side.
+    """
+    FrontierExplorer = ...
+    r"""
+    Maps the player grid from its rays and drives to the nearest unexplored frontier.
+    """
+    Oracle = ...
+    r"""
+    Plans on the true grid with A* to fetch each flag and deliver it to a capture point.
+    """
+    Teleop = ...
+    r"""
+    Driven from the keyboard in the window: WASD to move, Space to pick up a flag and E to
+    drop it. Maps the player grid from its rays like `FrontierExplorer`.
+    """
 
 class EntityType(Enum):
     r"""
@@ -329,7 +1238,88 @@ class EntityType(Enum):
     CapturePoint = ...
     Unknown = ...
 
+class FlagStatus(Enum):
+    r"""
+    Where a flag is in its life cycle.
+    """
+    Dropped = ...
+    PickedUp = ...
+    Captured = ...
+
+class GridOverlay(Enum):
+    r"""
+    Which occupancy grid, if any, to draw in a rendered frame.
+    """
+    None = ...
+    Player = ...
+    r"""
+    The grid the policy builds.
+    """
+    True = ...
+    r"""
+    The ground truth grid.
+    """
+
+class ObservationFrame(Enum):
+    r"""
+    What part of the world an image observation shows.
+    """
+    Global = ...
+    r"""
+    The whole maze, scaled to fit the image.
+    """
+    Egocentric = ...
+    r"""
+    A window of `view_size` world units centered on the agent. The map does not rotate,
+    since the agent has no heading.
+    """
+
+class PolicyErrorMode(Enum):
+    r"""
+    What to do when the policy raises an exception.
+    """
+    Stop = ...
+    r"""
+    Stop the simulation and re-raise the exception from `run()` or `StateQueue.get()`.
+    """
+    Restart = ...
+    r"""
+    Log the exception and call the policy's `startup` again with the next state before
+    asking it for more actions. Failed startups are retried with a growing delay, and the
+    simulation stops as with `Stop` after eight of them in a row.
+    """
+    NoOp = ...
+    r"""
+    Log the exception and stop the agent, as if it had asked for zero velocity.
+    """
+
+class RecordingFormat(Enum):
+    r"""
+    How recorded frames are written to `RecordingConfig.output_dir`.
+    """
+    PngSequence = ...
+    r"""
+    One `frame_00000.png` file per frame.
+    """
+    Gif = ...
+    r"""
+    A single looping `recording.gif`.
+    """
+
+class ViewLayout(Enum):
+    r"""
+    How the agent view shares the window with the top-down view.
+    """
+    PictureInPicture = ...
+    r"""
+    A small view in the bottom right corner of the top-down one.
+    """
+    SideBySide = ...
+    r"""
+    The top-down view on the left half of the window and the agent view on the right.
+    """
+
 def parse_config(config_path:builtins.str) -> MazeConfig: ...
 
-def run(config:MazeConfig, policy:typing.Any) -> typing.Optional[StateQueue]: ...
+def run(config:MazeConfig, policy:typing.Optional[typing.Any]=None) -> typing.Optional[StateQueue]: ...
 
@@ -1,8 +1,11 @@
+use std::collections::VecDeque;
+
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use derivative::Derivative;
 use pyo3::prelude::*;
 use pyo3_stub_gen::derive::gen_stub_pyclass_complex_enum;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     agent::{AGENT_RAYCAST_MAX_DISTANCE, COLLISION_LAYER_AGENT, NUM_AGENT_RAYS},
@@ -21,10 +24,113 @@ pub struct Agent;
 #[reflect(Component)]
 pub struct GhostAgent;
 
+/// Where the agent started, in world (x, y).
+#[derive(Debug, Clone, Copy, Default, Resource)]
+pub struct AgentSpawn(pub Vec2);
+
 #[derive(Debug, Clone, Default, Component, Reflect)]
 #[reflect(Component)]
 pub struct RayCasters(pub Vec<RayCaster>);
 
+/// Gizmos for the agent's rays, toggled with R.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct RayGizmos;
+
+/// Gizmos for the true and estimated trajectories, toggled with L.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct TrailGizmos;
+
+/// Gizmos for the policy's particles and uncertainty ellipse, toggled with C.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct UncertaintyGizmos;
+
+/// How unsure the policy is of its position estimate, from its optional `particles` and
+/// `covariance` attributes.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct PoseUncertainty {
+    /// Particle positions and their weights.
+    pub particles: Vec<(Vec2, f32)>,
+    pub covariance: Option<Mat2>,
+}
+
+impl PoseUncertainty {
+    /// The covariance the policy gave or, failing that, the weighted covariance of its particles.
+    pub fn covariance(&self) -> Option<Mat2> {
+        if self.covariance.is_some() {
+            return self.covariance;
+        }
+        let total = self.particles.iter().map(|&(_, w)| w).sum::<f32>();
+        if self.particles.len() < 2 || total <= 0.0 {
+            return None;
+        }
+        let mean = self.particles.iter().map(|&(p, w)| p * w).sum::<Vec2>() / total;
+        let sum = self.particles.iter().fold(Mat2::ZERO, |sum, &(p, w)| {
+            let d = p - mean;
+            sum + Mat2::from_cols(d * d.x, d * d.y) * w
+        });
+        Some(sum / total)
+    }
+}
+
+/// Running NEES (normalized estimation error squared) of the policy's position estimate
+/// against its own covariance. A consistent 2D estimator averages 2.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct EstimateConsistency {
+    pub samples: u32,
+    pub sum: f32,
+    /// Samples inside the two-sided 95% interval of a chi-squared distribution with 2 degrees
+    /// of freedom.
+    pub within_bounds: u32,
+}
+
+impl EstimateConsistency {
+    const BOUNDS: (f32, f32) = (0.0506, 7.378);
+
+    /// Records the NEES of `error` under `covariance`, unless the covariance is singular.
+    pub fn record(&mut self, error: Vec2, covariance: Mat2) -> Option<f32> {
+        if covariance.determinant().abs() <= f32::EPSILON {
+            return None;
+        }
+        let nees = error.dot(covariance.inverse() * error);
+        self.samples += 1;
+        self.sum += nees;
+        if (Self::BOUNDS.0..=Self::BOUNDS.1).contains(&nees) {
+            self.within_bounds += 1;
+        }
+        Some(nees)
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.sum / self.samples.max(1) as f32
+    }
+
+    /// The fraction of samples within the 95% bounds, which should be close to 0.95.
+    pub fn within_fraction(&self) -> f32 {
+        self.within_bounds as f32 / self.samples.max(1) as f32
+    }
+}
+
+/// Where the agent and its ghost have been, oldest first, spaced `TRAIL_SPACING` apart.
+#[derive(Resource, Default)]
+pub struct Trails {
+    pub agent: VecDeque<Vec3>,
+    pub ghost_agent: VecDeque<Vec3>,
+}
+
+/// Wall contacts of an agent, updated every frame from the physics contact graph.
+#[derive(Debug, Clone, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct CollisionReport {
+    /// Whether the agent is currently touching a wall.
+    pub touching: bool,
+    /// Contact normals in the XZ plane, pointing from the wall towards the agent.
+    pub normals: Vec<Vec2>,
+    /// Impacts since the last policy tick.
+    pub impacts: u32,
+    /// Impacts since the start of the run.
+    pub total_impacts: u32,
+}
+
 impl RayCasters {
     pub fn new(num_rays: u32, max_distance: f32) -> Self {
         let thetas = (0..num_rays).map(|i| i as f32 * (std::f32::consts::TAU / num_rays as f32));
@@ -47,9 +153,10 @@ impl RayCasters {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Reflect)]
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
 #[gen_stub_pyclass_complex_enum]
 #[pyclass(name = "Action")]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Action {
     Move { id: u32, velocity: (f32, f32) },
     PickupFlag { id: u32 },
@@ -67,6 +174,8 @@ pub struct AgentBundle {
     pub collision_layer: CollisionLayers,
     pub max_speed: MaxLinearSpeed,
     pub raycasters: RayCasters,
+    pub collision_events: CollisionEventsEnabled,
+    pub collision_report: CollisionReport,
 }
 
 impl AgentBundle {
@@ -99,6 +208,8 @@ impl Default for AgentBundle {
             character_controller: CharacterControllerBundle::new(Collider::cuboid(1.0, 1.0, 1.0)),
             collision_layer,
             raycasters: RayCasters::new(NUM_AGENT_RAYS, AGENT_RAYCAST_MAX_DISTANCE),
+            collision_events: CollisionEventsEnabled,
+            collision_report: CollisionReport::default(),
         }
     }
 }
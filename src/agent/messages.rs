@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+
+use crate::python::game_state::ActionResult;
+
+/// Written whenever an action from the policy has been applied, adjusted or refused.
+#[derive(Message, Clone, Debug)]
+pub struct ActionResultMessage {
+    pub agent_id: u32,
+    pub result: ActionResult,
+}
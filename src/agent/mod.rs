@@ -1,20 +1,31 @@
 mod components;
+mod messages;
 mod systems;
 mod visual;
 
+use std::time::Duration;
+
+use bevy::input::common_conditions::input_just_pressed;
 use bevy::prelude::*;
 use derivative::Derivative;
 use pyo3::prelude::*;
-use pyo3_stub_gen::derive::gen_stub_pyclass;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_enum};
 use serde::{Deserialize, Serialize};
 
 pub use components::*;
+pub use messages::*;
 
 use crate::core::{MazeConfig, StartupSets};
 
 pub const COLLISION_LAYER_AGENT: u32 = 1 << 1;
 pub const NUM_AGENT_RAYS: u32 = 16;
 pub const AGENT_RAYCAST_MAX_DISTANCE: f32 = 20.0;
+/// Minimum distance between recorded trail points.
+pub const TRAIL_SPACING: f32 = 0.25;
+/// Trail points kept per trail; older ones are dropped.
+pub const MAX_TRAIL_POINTS: usize = 4000;
+/// Particles drawn at most; larger particle sets are thinned out evenly.
+pub const MAX_DRAWN_PARTICLES: usize = 2000;
 
 #[gen_stub_pyclass]
 #[pyclass(name = "AgentConfig")]
@@ -44,6 +55,87 @@ pub struct AgentConfig {
     #[pyo3(get, set)]
     #[derivative(Default(value = "1.0"))]
     pub occupancy_grid_cell_size: f32,
+
+    #[pyo3(get, set)]
+    pub on_policy_error: PolicyErrorMode,
+
+    /// How long a single `get_action` call may take, in milliseconds. None means no limit.
+    #[pyo3(get, set)]
+    pub policy_budget_ms: Option<f32>,
+
+    #[pyo3(get, set)]
+    pub on_budget_overrun: BudgetOverrunMode,
+
+    /// Address of an out-of-process policy server, either `unix:/path/to/socket` or a TCP
+    /// address such as `127.0.0.1:5555`. When set, the policy passed to `run` is not used.
+    #[pyo3(get, set)]
+    pub policy_server: Option<String>,
+
+    /// The Rust policy that drives the agent when neither a Python policy nor `policy_server`
+    /// is given.
+    #[pyo3(get, set)]
+    pub builtin_policy: BuiltinPolicy,
+
+    /// With the teleop policy, a JSON Lines file to write every state and the action taken in it
+    /// to, as a demonstration for imitation learning. Nothing is written when None.
+    #[pyo3(get, set)]
+    pub demonstration_path: Option<String>,
+}
+
+/// What to do when the policy raises an exception.
+#[gen_stub_pyclass_enum]
+#[pyclass(name = "PolicyErrorMode", frozen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyErrorMode {
+    /// Stop the simulation and re-raise the exception from `run()` or `StateQueue.get()`.
+    #[default]
+    Stop,
+    /// Log the exception and call the policy's `startup` again with the next state before
+    /// asking it for more actions. Failed startups are retried with a growing delay, and the
+    /// simulation stops as with `Stop` after eight of them in a row.
+    Restart,
+    /// Log the exception and stop the agent, as if it had asked for zero velocity.
+    NoOp,
+}
+
+/// What to do with an action that took longer than `policy_budget_ms` to compute.
+#[gen_stub_pyclass_enum]
+#[pyclass(name = "BudgetOverrunMode", frozen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetOverrunMode {
+    /// Discard the late action.
+    #[default]
+    Skip,
+    /// Discard the late action and repeat the last action that was on time.
+    HoldLast,
+    /// Discard the late action and stop the agent.
+    ZeroVelocity,
+    /// End the run and mark the policy as disqualified.
+    Disqualify,
+}
+
+/// Reference policies implemented in Rust, used as baselines.
+#[gen_stub_pyclass_enum]
+#[pyclass(name = "BuiltinPolicy", frozen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinPolicy {
+    /// Heads for flags and capture points its rays can see, turning away from walls otherwise.
+    #[default]
+    Scripted,
+    /// Drives in a random direction for a random while, turning early if a wall gets close.
+    RandomWalk,
+    /// Keeps a wall on its right hand side.
+    WallFollower,
+    /// Maps the player grid from its rays and drives to the nearest unexplored frontier.
+    FrontierExplorer,
+    /// Plans on the true grid with A* to fetch each flag and deliver it to a capture point.
+    Oracle,
+    /// Driven from the keyboard in the window: WASD to move, Space to pick up a flag and E to
+    /// drop it. Maps the player grid from its rays like `FrontierExplorer`.
+    Teleop,
 }
 
 #[pymethods]
@@ -62,11 +154,44 @@ impl AgentConfig {
     }
 }
 
+impl AgentConfig {
+    /// `policy_budget_ms` as a duration.
+    pub fn policy_budget(&self) -> Option<Duration> {
+        self.policy_budget_ms
+            .map(|ms| Duration::from_secs_f32(ms.max(0.0) / 1000.0))
+    }
+}
+
 pub struct AgentPlugin;
 impl Plugin for AgentPlugin {
     fn build(&self, app: &mut App) {
+        app.add_message::<ActionResultMessage>();
         app.add_systems(PreStartup, spawn_agent_assets);
         app.add_systems(Startup, systems::spawn_agents.in_set(StartupSets::Agents));
+        app.add_systems(Update, systems::track_collisions);
+        app.init_resource::<PoseUncertainty>();
+        app.init_resource::<EstimateConsistency>();
+
+        if !app.world().resource::<MazeConfig>().headless {
+            app.init_gizmo_group::<RayGizmos>();
+            app.init_gizmo_group::<TrailGizmos>();
+            app.init_gizmo_group::<UncertaintyGizmos>();
+            app.init_resource::<Trails>();
+            app.add_systems(Startup, systems::hide_overlays);
+            app.add_systems(
+                Update,
+                (
+                    systems::toggle_gizmos::<RayGizmos>.run_if(input_just_pressed(KeyCode::KeyR)),
+                    systems::toggle_gizmos::<TrailGizmos>.run_if(input_just_pressed(KeyCode::KeyL)),
+                    systems::toggle_gizmos::<UncertaintyGizmos>
+                        .run_if(input_just_pressed(KeyCode::KeyC)),
+                    systems::record_trails,
+                    systems::draw_rays,
+                    systems::draw_trails,
+                    systems::draw_pose_uncertainty,
+                ),
+            );
+        }
     }
 }
 
@@ -1,13 +1,28 @@
+use std::collections::VecDeque;
+use std::f32::consts::FRAC_PI_2;
+
+use avian3d::prelude::*;
 use bevy::prelude::*;
 use pyo3::prelude::*;
 use rand::SeedableRng;
 use rand::seq::IndexedRandom;
 use rand_chacha::ChaCha20Rng;
 
-use crate::agent::{AGENT_RAYCAST_MAX_DISTANCE, GhostAgentBundle};
+use crate::agent::{
+    AGENT_RAYCAST_MAX_DISTANCE, Agent, AgentSpawn, CollisionReport, GhostAgent, GhostAgentBundle,
+    MAX_DRAWN_PARTICLES, MAX_TRAIL_POINTS, PoseUncertainty, RayCasters, RayGizmos, TRAIL_SPACING,
+    TrailGizmos, Trails, UncertaintyGizmos,
+};
 use crate::core::MazeConfig;
+use crate::flag::{CapturePoint, Flag};
 use crate::occupancy_grid::TrueGrid;
-use crate::python::game_state::EntityType;
+use crate::python::game_state::{EntityType, classify};
+use crate::scene::{MazeLayout, Wall};
+
+/// Lifts overlay lines off the ground so they are not hidden by it.
+const OVERLAY_HEIGHT: f32 = 0.05;
+/// Scales standard deviations to the 95% confidence ellipse of a 2D Gaussian, sqrt(5.991).
+const ELLIPSE_SCALE: f32 = 2.4477;
 
 use super::components::AgentBundle;
 use super::visual::AgentGraphicsAssets;
@@ -17,49 +32,46 @@ pub fn spawn_agents(
     graphics: Option<Res<AgentGraphicsAssets>>,
     config: Res<MazeConfig>,
     true_grid: ResMut<TrueGrid>,
+    layout: Option<Res<MazeLayout>>,
 ) {
-    let position = Python::attach(|py| {
-        let grid = true_grid.0.read().unwrap();
-        let py_obj = grid.borrow(py);
-
-        let free_positions: Vec<(f32, f32)> = py_obj
-            .grid
-            .iter()
-            .enumerate()
-            .filter_map(|(i, cell)| {
-                if cell.assignment == Some(EntityType::Empty) {
-                    let grid_col = i as u32 % py_obj.width as u32;
-                    let grid_row = i as u32 / py_obj.width as u32;
-                    let x = (grid_col as f32) * config.agent.occupancy_grid_cell_size
-                        + config.agent.occupancy_grid_cell_size / 2.0
-                        - config.maze_generation.width / 2.0;
-                    let y = (grid_row as f32) * config.agent.occupancy_grid_cell_size
-                        + config.agent.occupancy_grid_cell_size / 2.0
-                        - config.maze_generation.height / 2.0;
-                    Some((x, y))
-                } else {
-                    None
-                }
-            })
-            .collect();
+    let spawn = layout.and_then(|layout| layout.agent_spawn);
+    let position = spawn.unwrap_or_else(|| {
+        Python::attach(|py| {
+            let grid = true_grid.0.read().unwrap();
+            let py_obj = grid.borrow(py);
 
-        if free_positions.is_empty() {
-            panic!("No free positions available to spawn the agent");
-        }
+            let free_positions: Vec<(f32, f32)> = py_obj
+                .grid
+                .iter()
+                .enumerate()
+                .filter_map(|(i, cell)| {
+                    if cell.assignment == Some(EntityType::Empty) {
+                        Some(py_obj.cell_to_world((i % py_obj.width, i / py_obj.width)))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
 
-        let mut rng = ChaCha20Rng::from_seed({
-            let mut arr = [0u8; 32];
-            let seed = config
-                .maze_generation
-                .seed
-                .expect("Should have generated a seed before the map generation");
-            arr[..4].copy_from_slice(&seed.to_le_bytes());
-            arr
-        });
-        free_positions.choose(&mut rng).copied().unwrap()
+            if free_positions.is_empty() {
+                panic!("No free positions available to spawn the agent");
+            }
+
+            let mut rng = ChaCha20Rng::from_seed({
+                let mut arr = [0u8; 32];
+                let seed = config
+                    .maze_generation
+                    .seed
+                    .expect("Should have generated a seed before the map generation");
+                arr[..4].copy_from_slice(&seed.to_le_bytes());
+                arr
+            });
+            free_positions.choose(&mut rng).copied().unwrap()
+        })
     });
 
     info!("Spawning agent at position: {:?}", position);
+    commands.insert_resource(AgentSpawn(position.into()));
 
     let entity = commands
         .spawn(AgentBundle::new(
@@ -89,3 +101,206 @@ pub fn spawn_agents(
         ));
     }
 }
+
+pub fn track_collisions(
+    mut collision_starts: MessageReader<CollisionStart>,
+    collisions: Collisions,
+    walls: Query<(), With<Wall>>,
+    mut agents: Query<(Entity, &mut CollisionReport), With<Agent>>,
+) {
+    let started = collision_starts.read().copied().collect::<Vec<_>>();
+
+    for (entity, mut report) in &mut agents {
+        for event in &started {
+            let other = if event.collider1 == entity {
+                event.collider2
+            } else if event.collider2 == entity {
+                event.collider1
+            } else {
+                continue;
+            };
+
+            if walls.contains(other) {
+                report.impacts += 1;
+                report.total_impacts += 1;
+            }
+        }
+
+        // Manifold normals point from collider1 to collider2, flip them so they face the agent.
+        let normals = collisions
+            .collisions_with(entity)
+            .filter(|pair| pair.is_touching())
+            .filter_map(|pair| {
+                if pair.collider1 == entity && walls.contains(pair.collider2) {
+                    Some((pair, -1.0))
+                } else if pair.collider2 == entity && walls.contains(pair.collider1) {
+                    Some((pair, 1.0))
+                } else {
+                    None
+                }
+            })
+            .flat_map(|(pair, sign)| {
+                pair.manifolds
+                    .iter()
+                    .map(move |manifold| (manifold.normal * sign).xz())
+            })
+            .collect::<Vec<_>>();
+
+        report.touching = !normals.is_empty();
+        report.normals = normals;
+    }
+}
+
+/// Starts with the overlays hidden, like the grids.
+pub fn hide_overlays(mut store: ResMut<GizmoConfigStore>) {
+    store.config_mut::<RayGizmos>().0.enabled = false;
+    store.config_mut::<TrailGizmos>().0.enabled = false;
+}
+
+pub fn toggle_gizmos<T: GizmoConfigGroup>(mut store: ResMut<GizmoConfigStore>) {
+    let (config, _) = store.config_mut::<T>();
+    config.enabled = !config.enabled;
+}
+
+pub fn record_trails(
+    mut trails: ResMut<Trails>,
+    agent: Query<&Transform, (With<Agent>, Without<GhostAgent>)>,
+    ghost_agent: Query<&Transform, (With<GhostAgent>, Without<Agent>)>,
+) {
+    if let Ok(transform) = agent.single() {
+        push_trail_point(&mut trails.agent, transform.translation);
+    }
+    if let Ok(transform) = ghost_agent.single() {
+        push_trail_point(&mut trails.ghost_agent, transform.translation);
+    }
+}
+
+fn push_trail_point(trail: &mut VecDeque<Vec3>, position: Vec3) {
+    let position = position.with_y(OVERLAY_HEIGHT);
+    if trail
+        .back()
+        .is_some_and(|last| last.distance(position) < TRAIL_SPACING)
+    {
+        return;
+    }
+    if trail.len() >= MAX_TRAIL_POINTS {
+        trail.pop_front();
+    }
+    trail.push_back(position);
+}
+
+pub fn draw_trails(trails: Res<Trails>, mut gizmos: Gizmos<TrailGizmos>) {
+    gizmos.linestrip(trails.agent.iter().copied(), Color::srgb(1.0, 0.2, 0.2));
+    gizmos.linestrip(
+        trails.ghost_agent.iter().copied(),
+        Color::srgb(0.2, 0.8, 1.0),
+    );
+}
+
+/// Draws the policy's particles, larger and more opaque the heavier they are, and the 95%
+/// confidence ellipse of its covariance around the ghost agent.
+pub fn draw_pose_uncertainty(
+    uncertainty: Res<PoseUncertainty>,
+    ghost_agent: Query<&Transform, With<GhostAgent>>,
+    mut gizmos: Gizmos<UncertaintyGizmos>,
+) {
+    // Gizmo circles lie in the xy plane unless rotated onto the ground.
+    let flat = Quat::from_rotation_x(FRAC_PI_2);
+    let color = Color::srgb(0.2, 0.8, 1.0);
+
+    let particles = &uncertainty.particles;
+    let max_weight = particles.iter().map(|&(_, w)| w).fold(0.0, f32::max);
+    let step = particles.len().div_ceil(MAX_DRAWN_PARTICLES).max(1);
+    for &(position, weight) in particles.iter().step_by(step) {
+        let relative = if max_weight > 0.0 {
+            weight / max_weight
+        } else {
+            1.0
+        };
+        gizmos
+            .circle(
+                Isometry3d::new(Vec3::new(position.x, OVERLAY_HEIGHT, position.y), flat),
+                0.05 + 0.2 * relative,
+                color.with_alpha(0.2 + 0.8 * relative),
+            )
+            .resolution(8);
+    }
+
+    let (Some(covariance), Ok(ghost)) = (uncertainty.covariance(), ghost_agent.single()) else {
+        return;
+    };
+    // Eigen decomposition of the symmetric covariance: the ellipse axes and their variances.
+    let (a, b, c) = (
+        covariance.x_axis.x,
+        covariance.x_axis.y,
+        covariance.y_axis.y,
+    );
+    let mid = (a + c) * 0.5;
+    let spread = (((a - c) * 0.5).powi(2) + b * b).sqrt();
+    let half_size = Vec2::new(mid + spread, mid - spread)
+        .max(Vec2::ZERO)
+        .map(f32::sqrt)
+        * ELLIPSE_SCALE;
+    let angle = 0.5 * (2.0 * b).atan2(a - c);
+    gizmos.ellipse(
+        Isometry3d::new(
+            ghost.translation.with_y(OVERLAY_HEIGHT),
+            flat * Quat::from_rotation_z(angle),
+        ),
+        half_size,
+        color,
+    );
+}
+
+/// Draws every ray up to what it hits, colored by the kind of entity hit and fading with
+/// distance. Rays that hit nothing are gray.
+#[allow(clippy::type_complexity)]
+pub fn draw_rays(
+    spatial_query: SpatialQuery,
+    agents: Query<(&Transform, &RayCasters, Option<&Children>), With<Agent>>,
+    kinds: Query<(Option<&Wall>, Option<&Flag>, Option<&CapturePoint>)>,
+    mut gizmos: Gizmos<RayGizmos>,
+) {
+    for (transform, raycasters, children) in &agents {
+        // Rays pass through the flag the agent carries, as in `collect_agent_state`.
+        let carried = children
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&child| matches!(kinds.get(child), Ok((_, Some(_), _))))
+            .collect::<Vec<_>>();
+
+        for raycaster in &raycasters.0 {
+            let origin = transform.translation + raycaster.origin;
+            let hit = spatial_query.cast_ray(
+                origin,
+                raycaster.direction,
+                raycaster.max_distance,
+                raycaster.solid,
+                &raycaster
+                    .query_filter
+                    .clone()
+                    .with_excluded_entities(carried.clone()),
+            );
+
+            let (distance, color) = match hit {
+                Some(hit) => {
+                    let color = match classify(hit.entity, &kinds) {
+                        EntityType::Wall => Color::srgb(1.0, 0.5, 0.0),
+                        EntityType::Flag => Color::srgb(0.2, 0.4, 1.0),
+                        EntityType::CapturePoint => Color::srgb(0.8, 0.1, 0.6),
+                        EntityType::Empty | EntityType::Unknown => Color::srgb(1.0, 1.0, 0.0),
+                    };
+                    (hit.distance, color)
+                }
+                None => (raycaster.max_distance, Color::srgb(0.6, 0.6, 0.6)),
+            };
+            let fade = 1.0 - 0.7 * distance / raycaster.max_distance.max(f32::EPSILON);
+            let end = origin + *raycaster.direction * distance;
+            gizmos.line(origin, end, color.with_alpha(fade));
+            if hit.is_some() {
+                gizmos.sphere(Isometry3d::from_translation(end), 0.15, color);
+            }
+        }
+    }
+}
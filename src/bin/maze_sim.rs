@@ -0,0 +1,65 @@
+use anyhow::{Context, bail};
+
+const USAGE: &str = "\
+Usage: maze-sim <config.yaml> [options]
+
+Options:
+  --headless               Run without a window, regardless of the config
+  --windowed               Open a window, regardless of the config
+  --policy-server <ADDR>   Connect to a policy server (unix:/path or host:port)
+  --duration <SECONDS>     Stop after this many seconds of simulated time
+  -h, --help               Print this message
+
+Without --policy-server (or agent.policy_server in the config), the agent is driven by the
+built-in policy named in agent.builtin_policy: scripted, random_walk, wall_follower,
+frontier_explorer, oracle or teleop.";
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+
+    let mut config_path = None;
+    let mut headless = None;
+    let mut policy_server = None;
+    let mut duration = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            "--headless" => headless = Some(true),
+            "--windowed" => headless = Some(false),
+            "--policy-server" => {
+                policy_server = Some(args.next().context("--policy-server needs an address")?);
+            }
+            "--duration" => {
+                let seconds = args
+                    .next()
+                    .context("--duration needs a number of seconds")?;
+                duration = Some(
+                    seconds
+                        .parse::<f32>()
+                        .with_context(|| format!("Invalid duration: {seconds}"))?,
+                );
+            }
+            _ if arg.starts_with('-') => bail!("Unknown option: {arg}\n\n{USAGE}"),
+            _ if config_path.is_none() => config_path = Some(arg),
+            _ => bail!("Unexpected argument: {arg}\n\n{USAGE}"),
+        }
+    }
+
+    let Some(config_path) = config_path else {
+        bail!("Missing config file\n\n{USAGE}");
+    };
+
+    let mut config = maze_core::load_config(&config_path)?;
+    if let Some(headless) = headless {
+        config.headless = headless;
+    }
+    if policy_server.is_some() {
+        config.agent.policy_server = policy_server;
+    }
+
+    maze_core::run_standalone(config, duration)
+}
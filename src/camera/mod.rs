@@ -1,13 +1,23 @@
 mod systems;
 
-use bevy::input::common_conditions::input_pressed;
+use bevy::input::common_conditions::input_just_pressed;
 use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::input::egui_wants_any_pointer_input;
 
 use derivative::Derivative;
 use pyo3::prelude::*;
-use pyo3_stub_gen::derive::gen_stub_pyclass;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_enum};
 use serde::{Deserialize, Serialize};
 
+use crate::core::MazeConfig;
+
+pub const FOLLOW_AGENT_KEY: KeyCode = KeyCode::KeyF;
+pub const FIT_MAZE_KEY: KeyCode = KeyCode::KeyH;
+pub const AGENT_VIEW_KEY: KeyCode = KeyCode::KeyV;
+/// The render layer of what only the agent view shows in `AgentView::Belief`: the player grid
+/// and the estimated pose.
+pub const BELIEF_LAYER: usize = 1;
+
 #[gen_stub_pyclass]
 #[pyclass(name = "CameraConfig")]
 #[derive(Debug, Clone, Resource, Reflect, Derivative, Serialize, Deserialize)]
@@ -15,9 +25,112 @@ use serde::{Deserialize, Serialize};
 #[serde(default)]
 #[reflect(Resource)]
 pub struct CameraConfig {
+    /// Zoom relative to fitting the whole maze in the window: 0.5 shows half as much. The sign
+    /// sets the orientation of the view.
     #[pyo3(get, set)]
-    #[derivative(Default(value = "-0.15"))]
-    pub scale: f32,
+    #[derivative(Default(value = "-1.0"))]
+    pub zoom: f32,
+
+    /// Deprecated in favor of `zoom`. World units per window pixel, signed like `zoom`. When
+    /// set, the view starts out at the zoom showing that much of the maze.
+    #[pyo3(get, set)]
+    pub scale: Option<f32>,
+
+    /// The closest and farthest zoom allowed, as magnitudes of `zoom`.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "0.05"))]
+    pub min_zoom: f32,
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "2.0"))]
+    pub max_zoom: f32,
+
+    /// How fast the arrow keys pan, in window heights per second.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "0.75"))]
+    pub pan_speed: f32,
+
+    /// How much holding + or - zooms per second, as a factor.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "2.0"))]
+    pub zoom_speed: f32,
+
+    /// How much one notch of the scroll wheel zooms, as a factor.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "1.15"))]
+    pub scroll_zoom: f32,
+
+    /// How quickly the camera catches up with where it is asked to be, per second. Zero moves
+    /// it there at once.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "12.0"))]
+    pub smoothing: f32,
+
+    /// Start out following the agent, which F toggles.
+    #[pyo3(get, set)]
+    pub follow_agent: bool,
+
+    /// What the second view, centered on the agent, shows at the start. V cycles through the
+    /// options.
+    #[pyo3(get, set)]
+    pub agent_view: AgentView,
+
+    #[pyo3(get, set)]
+    pub agent_view_layout: ViewLayout,
+
+    /// The width and height of the picture-in-picture view, as a fraction of the window's.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "0.3"))]
+    pub agent_view_size: f32,
+
+    /// How much of the maze the agent view spans vertically, in world units.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "30.0"))]
+    pub agent_view_extent: f32,
+}
+
+/// What the view that follows the agent shows.
+#[gen_stub_pyclass_enum]
+#[pyclass(name = "AgentView", frozen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentView {
+    /// No agent view.
+    #[default]
+    Off,
+    /// The true scene around the agent.
+    Scene,
+    /// Only the player grid and the estimated pose, which is what the agent believes.
+    Belief,
+}
+
+impl AgentView {
+    pub fn next(self) -> Self {
+        match self {
+            AgentView::Off => AgentView::Scene,
+            AgentView::Scene => AgentView::Belief,
+            AgentView::Belief => AgentView::Off,
+        }
+    }
+}
+
+/// How the agent view shares the window with the top-down view.
+#[gen_stub_pyclass_enum]
+#[pyclass(name = "ViewLayout", frozen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewLayout {
+    /// A small view in the bottom right corner of the top-down one.
+    #[default]
+    PictureInPicture,
+    /// The top-down view on the left half of the window and the agent view on the right.
+    SideBySide,
+}
+
+impl CameraConfig {
+    /// The sign of the zoom, which sets the orientation of the views. A deprecated `scale` wins.
+    pub fn orientation(&self) -> f32 {
+        self.scale.unwrap_or(self.zoom).signum()
+    }
 }
 
 #[pymethods]
@@ -36,17 +149,89 @@ impl CameraConfig {
     }
 }
 
+/// The top-down view of the maze.
+#[derive(Debug, Clone, Copy, Default, Component)]
+pub struct MainCamera;
+
+/// The view that follows the agent.
+#[derive(Debug, Clone, Copy, Default, Component)]
+pub struct AgentCamera;
+
+/// What the agent view shows now.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct ActiveAgentView(pub AgentView);
+
+/// Where the controls ask the main camera to be. The camera eases towards it.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CameraTarget {
+    /// The point in world (x, y) at the center of the view.
+    pub center: Vec2,
+    /// The orthographic scale, which is the zoom, signed like `CameraConfig.zoom`.
+    pub scale: f32,
+    pub follow_agent: bool,
+}
+
+impl CameraTarget {
+    /// Frames the whole maze, without following the agent.
+    pub fn fit(config: &CameraConfig) -> Self {
+        Self {
+            center: Vec2::ZERO,
+            scale: config.zoom.abs() * config.orientation(),
+            follow_agent: false,
+        }
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the magnitude of the scale within the
+    /// configured bounds.
+    pub fn zoom(&mut self, factor: f32, config: &CameraConfig) {
+        let magnitude = (self.scale.abs() * factor).clamp(config.min_zoom, config.max_zoom);
+        self.scale = magnitude.copysign(self.scale);
+    }
+}
+
 pub struct CameraPlugin;
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, systems::setup_camera);
+        let config = app.world().resource::<MazeConfig>();
+        if config.headless {
+            return;
+        }
+        let config = config.camera.clone();
+        app.insert_resource(CameraTarget {
+            follow_agent: config.follow_agent,
+            ..CameraTarget::fit(&config)
+        });
+
+        app.insert_resource(ActiveAgentView(config.agent_view));
+
         app.add_systems(
-            Update,
+            Startup,
             (
-                systems::zoom_in.run_if(input_pressed(KeyCode::Equal)),
-                systems::zoom_out.run_if(input_pressed(KeyCode::Minus)),
-                systems::pan_camera,
+                (systems::setup_camera, systems::apply_deprecated_scale).chain(),
+                systems::setup_agent_camera,
             ),
         );
+        app.add_systems(PostStartup, systems::setup_belief_layer);
+        app.add_systems(
+            Update,
+            (
+                (
+                    systems::cycle_agent_view.run_if(input_just_pressed(AGENT_VIEW_KEY)),
+                    systems::toggle_follow_agent.run_if(input_just_pressed(FOLLOW_AGENT_KEY)),
+                    systems::fit_maze.run_if(input_just_pressed(FIT_MAZE_KEY)),
+                    systems::zoom_with_keys,
+                    systems::zoom_with_scroll.run_if(not(egui_wants_any_pointer_input)),
+                    systems::pan_with_keys,
+                    systems::pan_with_mouse.run_if(not(egui_wants_any_pointer_input)),
+                    systems::follow_agent,
+                ),
+                (
+                    systems::move_camera,
+                    systems::follow_agent_with_agent_camera,
+                    systems::update_viewports,
+                ),
+            )
+                .chain(),
+        );
     }
 }
@@ -1,56 +1,398 @@
-use bevy::prelude::*;
+use bevy::{
+    camera::{ScalingMode, Viewport, visibility::RenderLayers},
+    input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_inspector_egui::bevy_egui::{EguiContext, PrimaryEguiContext};
 
+use crate::agent::{Agent, GhostAgent};
+use crate::camera::{
+    ActiveAgentView, AgentCamera, AgentView, BELIEF_LAYER, CameraTarget, MainCamera, ViewLayout,
+};
 use crate::core::MazeConfig;
+use crate::editor::Editor;
+use crate::occupancy_grid::{GridVisualization, PlayerGrid};
+
+/// Extra room around the maze when the camera frames it, as a fraction of its size.
+const FRAMING_MARGIN: f32 = 0.05;
+/// Gap between the picture-in-picture view and the window's edges, in physical pixels.
+const VIEW_MARGIN: u32 = 10;
+/// Scroll distance of one wheel notch on devices that report pixels, such as touchpads.
+const PIXELS_PER_NOTCH: f32 = 50.0;
 
 pub fn setup_camera(mut commands: Commands, config: Res<MazeConfig>) {
-    if config.headless {
+    commands.spawn((
+        MainCamera,
+        // The HUD and egui windows belong to this view, not to whichever camera is drawn last.
+        IsDefaultUiCamera,
+        EguiContext::default(),
+        PrimaryEguiContext,
+        Camera3d::default(),
+        Transform::from_translation(Vec3::new(0.0, 10.0, 0.0)).looking_at(Vec3::ZERO, Vec3::NEG_Z),
+        Projection::from(OrthographicProjection {
+            scale: config.camera.zoom.abs() * config.camera.orientation(),
+            scaling_mode: ScalingMode::AutoMin {
+                min_width: config.maze_generation.width * (1.0 + FRAMING_MARGIN),
+                min_height: config.maze_generation.height * (1.0 + FRAMING_MARGIN),
+            },
+            ..OrthographicProjection::default_3d()
+        }),
+    ));
+}
+
+/// Turns a `CameraConfig.scale` from before `zoom` existed into the zoom that shows as much of
+/// the maze in the window.
+pub fn apply_deprecated_scale(
+    config: Res<MazeConfig>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut target: ResMut<CameraTarget>,
+    mut projection: Query<&mut Projection, With<MainCamera>>,
+) {
+    let Some(scale) = config.camera.scale else {
+        return;
+    };
+    let (Ok(window), Ok(mut projection)) = (windows.single(), projection.single_mut()) else {
+        return;
+    };
+    let Projection::Orthographic(ortho) = &mut *projection else {
+        return;
+    };
+
+    // How much of the maze fits vertically at zoom 1, following `ScalingMode::AutoMin`.
+    let size = window.size();
+    let ScalingMode::AutoMin {
+        min_width,
+        min_height,
+    } = ortho.scaling_mode
+    else {
+        return;
+    };
+    let fit_height = min_height.max(min_width * size.y / size.x);
+    // Within the bounds that `CameraTarget::zoom` keeps to, so the first scroll does not jump.
+    let zoom = (scale.abs() * size.y / fit_height)
+        .clamp(config.camera.min_zoom, config.camera.max_zoom)
+        .copysign(scale);
+    warn!(
+        "camera.scale is deprecated; use camera.zoom: {:.2} instead of scale: {}",
+        zoom, scale
+    );
+
+    ortho.scale = zoom;
+    target.scale = zoom;
+}
+
+/// The point on the ground under a position in the viewport.
+fn ground_point(camera: &Camera, transform: &GlobalTransform, position: Vec2) -> Option<Vec2> {
+    let ray = camera.viewport_to_world(transform, position).ok()?;
+    let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))?;
+    Some(ray.get_point(distance).xz())
+}
+
+pub fn toggle_follow_agent(mut target: ResMut<CameraTarget>) {
+    target.follow_agent = !target.follow_agent;
+}
+
+pub fn fit_maze(mut target: ResMut<CameraTarget>, config: Res<MazeConfig>) {
+    *target = CameraTarget::fit(&config.camera);
+}
+
+pub fn zoom_with_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    config: Res<MazeConfig>,
+    mut target: ResMut<CameraTarget>,
+) {
+    let direction = keys.pressed(KeyCode::Minus) as i32 - keys.pressed(KeyCode::Equal) as i32;
+    if direction == 0 {
         return;
     }
+    let factor = config
+        .camera
+        .zoom_speed
+        .powf(direction as f32 * time.delta_secs());
+    target.zoom(factor, &config.camera);
+}
 
+/// Zooms in on the point under the cursor.
+pub fn zoom_with_scroll(
+    scroll: Res<AccumulatedMouseScroll>,
+    config: Res<MazeConfig>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut target: ResMut<CameraTarget>,
+) {
+    let notches = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / PIXELS_PER_NOTCH,
+    };
+    if notches == 0.0 {
+        return;
+    }
+
+    let previous = target.scale;
+    target.zoom(config.camera.scroll_zoom.powf(-notches), &config.camera);
+    if target.follow_agent {
+        return;
+    }
+
+    let cursor = windows
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let anchor = cursor
+        .zip(camera.single().ok())
+        .and_then(|(cursor, (camera, transform))| ground_point(camera, transform, cursor));
+    if let Some(anchor) = anchor {
+        target.center = anchor + (target.center - anchor) * (target.scale / previous);
+    }
+}
+
+pub fn pan_with_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    config: Res<MazeConfig>,
+    projection: Query<&Projection, With<MainCamera>>,
+    mut target: ResMut<CameraTarget>,
+) {
+    let axis = |negative, positive| {
+        keys.pressed(positive) as i32 as f32 - keys.pressed(negative) as i32 as f32
+    };
+    let direction = Vec2::new(
+        axis(KeyCode::ArrowLeft, KeyCode::ArrowRight),
+        axis(KeyCode::ArrowUp, KeyCode::ArrowDown),
+    );
+    if direction == Vec2::ZERO {
+        return;
+    }
+    let Ok(Projection::Orthographic(ortho)) = projection.single() else {
+        return;
+    };
+
+    let view_height = ortho.area.height().abs();
+    target.center +=
+        direction.normalize() * config.camera.pan_speed * view_height * time.delta_secs();
+    target.follow_agent = false;
+}
+
+/// Drags the view with the middle mouse button, or the left one outside the editor.
+pub fn pan_with_mouse(
+    buttons: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    editor: Option<Res<Editor>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut target: ResMut<CameraTarget>,
+) {
+    let editing = editor.is_some_and(|editor| editor.enabled);
+    let dragging =
+        buttons.pressed(MouseButton::Middle) || (!editing && buttons.pressed(MouseButton::Left));
+    if !dragging || motion.delta == Vec2::ZERO {
+        return;
+    }
+    let Some(cursor) = windows
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    let Ok((camera, transform)) = camera.single() else {
+        return;
+    };
+
+    let now = ground_point(camera, transform, cursor);
+    let before = ground_point(camera, transform, cursor - motion.delta);
+    if let (Some(now), Some(before)) = (now, before) {
+        target.center += before - now;
+        target.follow_agent = false;
+    }
+}
+
+pub fn follow_agent(mut target: ResMut<CameraTarget>, agents: Query<&Transform, With<Agent>>) {
+    if !target.follow_agent {
+        return;
+    }
+    if let Ok(agent) = agents.single() {
+        target.center = agent.translation.xz();
+    }
+}
+
+/// Eases the main camera towards the target at a rate that does not depend on the frame rate.
+pub fn move_camera(
+    time: Res<Time>,
+    config: Res<MazeConfig>,
+    target: Res<CameraTarget>,
+    mut camera: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
+) {
+    let Ok((mut transform, mut projection)) = camera.single_mut() else {
+        return;
+    };
+    let t = if config.camera.smoothing > 0.0 {
+        1.0 - (-config.camera.smoothing * time.delta_secs()).exp()
+    } else {
+        1.0
+    };
+
+    let center = transform.translation.xz().lerp(target.center, t);
+    transform.translation.x = center.x;
+    transform.translation.z = center.y;
+    if let Projection::Orthographic(ortho) = &mut *projection {
+        ortho.scale += (target.scale - ortho.scale) * t;
+    }
+}
+
+pub fn setup_agent_camera(
+    mut commands: Commands,
+    config: Res<MazeConfig>,
+    view: Res<ActiveAgentView>,
+) {
     commands.spawn((
+        AgentCamera,
         Camera3d::default(),
+        Camera {
+            // Drawn over the top-down view.
+            order: 1,
+            is_active: view.0 != AgentView::Off,
+            ..default()
+        },
         Transform::from_translation(Vec3::new(0.0, 10.0, 0.0)).looking_at(Vec3::ZERO, Vec3::NEG_Z),
         Projection::from(OrthographicProjection {
-            scale: config.camera.scale,
+            // Oriented like the top-down view.
+            scale: config.camera.orientation(),
+            scaling_mode: ScalingMode::FixedVertical {
+                viewport_height: config.camera.agent_view_extent,
+            },
             ..OrthographicProjection::default_3d()
         }),
+        RenderLayers::layer(0),
     ));
 }
 
-pub fn zoom_in(mut query: Query<&mut Projection, With<Camera3d>>) {
-    for mut proj in query.iter_mut() {
-        if let Projection::Orthographic(ortho) = &mut *proj {
-            ortho.scale += 0.001;
-        }
+/// Puts a copy of the player grid overlay and the ghost agent on `BELIEF_LAYER`. The copy shares
+/// the overlay's material, so it is updated with it but not hidden with it.
+pub fn setup_belief_layer(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    config: Res<MazeConfig>,
+    grid: Option<Res<GridVisualization<PlayerGrid>>>,
+    ghost_agents: Query<Entity, With<GhostAgent>>,
+) {
+    if let Some(grid) = grid {
+        let mesh = meshes.add(
+            Plane3d::default()
+                .mesh()
+                .size(config.maze_generation.width, config.maze_generation.height),
+        );
+        commands.spawn((
+            Mesh3d(mesh),
+            MeshMaterial3d(grid.material.clone()),
+            // On the ground, so that the ghost agent stands on it.
+            Transform::default(),
+            RenderLayers::layer(BELIEF_LAYER),
+            Name::new("BeliefPlane"),
+        ));
+    }
+    for entity in &ghost_agents {
+        commands
+            .entity(entity)
+            .insert(RenderLayers::from_layers(&[0, BELIEF_LAYER]));
     }
 }
 
-pub fn zoom_out(mut query: Query<&mut Projection, With<Camera3d>>) {
-    for mut proj in query.iter_mut() {
-        if let Projection::Orthographic(ortho) = &mut *proj {
-            ortho.scale -= 0.001;
-        }
-    }
+pub fn cycle_agent_view(mut view: ResMut<ActiveAgentView>) {
+    view.0 = view.0.next();
 }
 
-pub fn pan_camera(
-    mut query: Query<&mut Transform, With<Camera3d>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+/// Keeps the agent camera over the agent or, in the belief view, over its estimated pose.
+pub fn follow_agent_with_agent_camera(
+    view: Res<ActiveAgentView>,
+    agents: Query<&Transform, (With<Agent>, Without<AgentCamera>)>,
+    ghost_agents: Query<&Transform, (With<GhostAgent>, Without<AgentCamera>)>,
+    mut cameras: Query<&mut Transform, With<AgentCamera>>,
 ) {
-    let mut direction = Vec3::ZERO;
-    if keyboard_input.pressed(KeyCode::ArrowUp) {
-        direction.z -= 1.0;
+    let agent = match view.0 {
+        AgentView::Belief => ghost_agents.single(),
+        AgentView::Off | AgentView::Scene => agents.single(),
+    };
+    let Ok(agent) = agent else {
+        return;
+    };
+    for mut transform in &mut cameras {
+        transform.translation.x = agent.translation.x;
+        transform.translation.z = agent.translation.z;
     }
-    if keyboard_input.pressed(KeyCode::ArrowDown) {
-        direction.z += 1.0;
+}
+
+fn set_viewport(camera: &mut Camera, viewport: Option<(UVec2, UVec2)>) {
+    let current = camera
+        .viewport
+        .as_ref()
+        .map(|viewport| (viewport.physical_position, viewport.physical_size));
+    if current != viewport {
+        camera.viewport = viewport.map(|(physical_position, physical_size)| Viewport {
+            physical_position,
+            physical_size,
+            ..default()
+        });
     }
-    if keyboard_input.pressed(KeyCode::ArrowLeft) {
-        direction.x -= 1.0;
+}
+
+/// Lays out the top-down and agent views in the window, following its size and the agent view
+/// that is shown.
+#[allow(clippy::type_complexity)]
+pub fn update_viewports(
+    config: Res<MazeConfig>,
+    view: Res<ActiveAgentView>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut main_camera: Query<&mut Camera, (With<MainCamera>, Without<AgentCamera>)>,
+    mut agent_camera: Query<
+        (&mut Camera, &mut RenderLayers),
+        (With<AgentCamera>, Without<MainCamera>),
+    >,
+) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let size = window.physical_size();
+    if size.x < 2 || size.y < 2 {
+        return;
     }
-    if keyboard_input.pressed(KeyCode::ArrowRight) {
-        direction.x += 1.0;
+
+    let (main_viewport, agent_viewport) = match (view.0, config.camera.agent_view_layout) {
+        (AgentView::Off, _) => (None, None),
+        (_, ViewLayout::PictureInPicture) => {
+            let fraction = config.camera.agent_view_size.clamp(0.05, 1.0);
+            let view_size = (size.as_vec2() * fraction).as_uvec2().max(UVec2::ONE);
+            let position = size.saturating_sub(view_size + UVec2::splat(VIEW_MARGIN));
+            (None, Some((position, view_size)))
+        }
+        (_, ViewLayout::SideBySide) => {
+            let left = UVec2::new(size.x / 2, size.y);
+            let right = UVec2::new(size.x - left.x, size.y);
+            (
+                Some((UVec2::ZERO, left)),
+                Some((UVec2::new(left.x, 0), right)),
+            )
+        }
+    };
+
+    if let Ok(mut camera) = main_camera.single_mut() {
+        set_viewport(&mut camera, main_viewport);
     }
-    for mut transform in query.iter_mut() {
-        transform.translation += direction;
+    if let Ok((mut camera, mut layers)) = agent_camera.single_mut() {
+        let active = view.0 != AgentView::Off;
+        if camera.is_active != active {
+            camera.is_active = active;
+        }
+        set_viewport(&mut camera, agent_viewport);
+
+        let wanted = match view.0 {
+            AgentView::Belief => RenderLayers::layer(BELIEF_LAYER),
+            _ => RenderLayers::layer(0),
+        };
+        if *layers != wanted {
+            *layers = wanted;
+        }
     }
 }
@@ -1,7 +1,9 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
 
+use crate::agent::{Action, ActionResultMessage};
 use crate::character_controller::MaxLinearSpeed;
+use crate::python::game_state::ActionResult;
 
 use super::components::{CharacterController, Grounded};
 use super::messages::MovementMessage;
@@ -23,6 +25,7 @@ pub fn update_grounded(
 #[allow(clippy::type_complexity)]
 pub fn movement(
     mut movement_event_reader: MessageReader<MovementMessage>,
+    mut results: MessageWriter<ActionResultMessage>,
     mut controllers: Query<(
         Entity,
         Option<&MaxLinearSpeed>,
@@ -37,29 +40,54 @@ pub fn movement(
         {
             match *event {
                 MovementMessage::TranslateById(id, velocity) => {
-                    if is_grounded && entity.index() == id {
-                        if let Some(max_speed) = max_speed {
-                            let speed = velocity.length();
-                            if speed > max_speed.0 {
-                                let scale = max_speed.0 / speed;
-                                linear_velocity.x = velocity.x * scale;
-                                linear_velocity.z = velocity.y * scale;
+                    if entity.index() != id {
+                        continue;
+                    }
 
-                                eprintln!(
-                                    "Agent {} attemped to move too quickly. Capping speed {} to max {} (scale {})",
-                                    entity.index(),
-                                    speed,
-                                    max_speed.0,
-                                    scale
-                                );
+                    let action = Action::Move {
+                        id,
+                        velocity: velocity.into(),
+                    };
 
-                                continue;
-                            }
-                        }
+                    // Airborne agents cannot steer, as with rotation below.
+                    if !is_grounded {
+                        debug!("Rejected movement: agent {} is not grounded", id);
+                        results.write(ActionResultMessage {
+                            agent_id: id,
+                            result: ActionResult::rejected(action, "agent is not grounded"),
+                        });
+                        continue;
+                    }
 
-                        linear_velocity.x = velocity.x;
-                        linear_velocity.z = velocity.y;
+                    if let Some(max_speed) = max_speed {
+                        let speed = velocity.length();
+                        if speed > max_speed.0 {
+                            let scale = max_speed.0 / speed;
+                            linear_velocity.x = velocity.x * scale;
+                            linear_velocity.z = velocity.y * scale;
+
+                            debug!(
+                                "Agent {} attempted to move too quickly. Capping speed {} to max {} (scale {})",
+                                id, speed, max_speed.0, scale
+                            );
+                            results.write(ActionResultMessage {
+                                agent_id: id,
+                                result: ActionResult::clamped(
+                                    action,
+                                    format!("speed {:.2} capped to {:.2}", speed, max_speed.0),
+                                ),
+                            });
+
+                            continue;
+                        }
                     }
+
+                    linear_velocity.x = velocity.x;
+                    linear_velocity.z = velocity.y;
+                    results.write(ActionResultMessage {
+                        agent_id: id,
+                        result: ActionResult::accepted(action),
+                    });
                 }
                 MovementMessage::RotateById(id, omega) => {
                     if is_grounded && entity.index() == id {
@@ -7,10 +7,16 @@ use serde::{Deserialize, Serialize};
 use crate::agent;
 use crate::camera;
 use crate::character_controller;
+use crate::dashboard;
+use crate::debug_draw;
+use crate::editor;
 use crate::flag;
 use crate::interaction_range;
 use crate::occupancy_grid;
+use crate::recording;
+use crate::reward;
 use crate::scene;
+use crate::teleop;
 
 #[gen_stub_pyclass]
 #[pyclass(name = "MazeConfig")]
@@ -30,11 +36,21 @@ pub struct MazeConfig {
     #[pyo3(get, set)]
     pub maze_generation: scene::MazeGenerationConfig,
     #[pyo3(get, set)]
+    pub reward: reward::RewardConfig,
+    #[pyo3(get, set)]
+    pub recording: recording::RecordingConfig,
+    #[pyo3(get, set)]
+    pub image_observation: recording::ImageObservationConfig,
+    #[pyo3(get, set)]
     pub debug: bool,
     #[pyo3(get, set)]
     pub headless: bool,
     #[pyo3(get, set)]
     pub use_true_map: bool,
+    /// Fill in `GameState.privileged_info` with ground truth the agent could not sense, for
+    /// debugging and privileged training. Policies under evaluation should not rely on it.
+    #[pyo3(get, set)]
+    pub privileged_info: bool,
 }
 
 #[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -59,6 +75,17 @@ impl MazeConfig {
         })
     }
 }
+impl MazeConfig {
+    /// The number of occupancy grid cells along x and y, covering the maze.
+    pub fn occupancy_grid_size(&self) -> (usize, usize) {
+        let cell_size = self.agent.occupancy_grid_cell_size;
+        (
+            (self.maze_generation.width / cell_size).round() as usize,
+            (self.maze_generation.height / cell_size).round() as usize,
+        )
+    }
+}
+
 pub struct MazePlugin {
     pub config: MazeConfig,
 }
@@ -70,10 +97,16 @@ impl Plugin for MazePlugin {
         app.add_plugins((
             camera::CameraPlugin,
             character_controller::CharacterControllerPlugin,
+            dashboard::DashboardPlugin,
+            debug_draw::DebugDrawPlugin,
+            editor::EditorPlugin,
             agent::AgentPlugin,
             flag::FlagPlugin,
             interaction_range::InteractionRangePlugin,
+            reward::RewardPlugin,
+            recording::RecordingPlugin,
             scene::ScenePlugin,
+            teleop::TeleopPlugin,
             occupancy_grid::OccupancyGridPlugin {
                 config: self.config.clone(),
             },
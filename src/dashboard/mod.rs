@@ -0,0 +1,79 @@
+//! A window of rolling plots of how the run is going, toggled with G: localization and mapping
+//! error, coverage, speed, policy latency and captured flags.
+
+mod systems;
+
+use std::collections::VecDeque;
+
+use bevy::input::common_conditions::input_just_pressed;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{EguiGlobalSettings, EguiPlugin, EguiPrimaryContextPass};
+
+use crate::core::MazeConfig;
+
+pub const TOGGLE_DASHBOARD_KEY: KeyCode = KeyCode::KeyG;
+/// Seconds between two samples of the metrics.
+pub const SAMPLE_INTERVAL: f32 = 0.1;
+/// Samples kept per metric, a minute's worth.
+pub const MAX_SAMPLES: usize = 600;
+
+/// One sample of every metric, taken `time` seconds into the run.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MetricSample {
+    pub time: f32,
+    /// Distance between the true and estimated positions.
+    pub localization_error: f32,
+    /// Percentage of player grid cells that disagree with the true grid.
+    pub mapping_error: f32,
+    /// Percentage of the reachable cells that the player grid has mapped.
+    pub coverage: f32,
+    pub speed: f32,
+    /// Mean latency of the policy calls since the previous sample, in milliseconds. Repeats the
+    /// previous value when there were none.
+    pub policy_latency_ms: f32,
+    pub flags_captured: f32,
+}
+
+/// The most recent `MAX_SAMPLES` samples, oldest first.
+#[derive(Resource, Debug, Default)]
+pub struct MetricHistory {
+    pub samples: VecDeque<MetricSample>,
+    /// How many of `PolicyStats.latencies` earlier samples have covered.
+    pub latencies_seen: usize,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Dashboard {
+    pub visible: bool,
+}
+
+pub struct DashboardPlugin;
+impl Plugin for DashboardPlugin {
+    fn build(&self, app: &mut App) {
+        if app.world().resource::<MazeConfig>().headless {
+            return;
+        }
+
+        // The debug plugin adds egui for the inspector.
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        // The main camera takes the egui context itself, since the agent view's camera could
+        // otherwise be picked.
+        app.insert_resource(EguiGlobalSettings {
+            auto_create_primary_context: false,
+            ..default()
+        });
+
+        app.init_resource::<Dashboard>();
+        app.init_resource::<MetricHistory>();
+        app.add_systems(
+            Update,
+            (
+                systems::toggle_dashboard.run_if(input_just_pressed(TOGGLE_DASHBOARD_KEY)),
+                systems::record_metrics,
+            ),
+        );
+        app.add_systems(EguiPrimaryContextPass, systems::draw_dashboard);
+    }
+}
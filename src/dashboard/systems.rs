@@ -0,0 +1,167 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, Stroke},
+};
+
+use crate::agent::{Agent, GhostAgent};
+use crate::dashboard::{Dashboard, MAX_SAMPLES, MetricHistory, MetricSample, SAMPLE_INTERVAL};
+use crate::flag::FlagCaptureCounts;
+use crate::occupancy_grid::{ExplorationTracker, MappingError};
+use crate::python::summary::PolicyStats;
+
+const PLOT_WIDTH: f32 = 280.0;
+const PLOT_HEIGHT: f32 = 48.0;
+
+/// The plots, top to bottom: name, unit, how to read the value from a sample and line color.
+#[allow(clippy::type_complexity)]
+const PLOTS: [(&str, &str, fn(&MetricSample) -> f32, Color32); 6] = [
+    (
+        "Localization Error",
+        "",
+        |s| s.localization_error,
+        Color32::from_rgb(255, 120, 120),
+    ),
+    (
+        "Mapping Error",
+        "%",
+        |s| s.mapping_error,
+        Color32::from_rgb(255, 190, 90),
+    ),
+    (
+        "Coverage",
+        "%",
+        |s| s.coverage,
+        Color32::from_rgb(120, 220, 120),
+    ),
+    ("Speed", "", |s| s.speed, Color32::from_rgb(120, 180, 255)),
+    (
+        "Policy Latency",
+        "ms",
+        |s| s.policy_latency_ms,
+        Color32::from_rgb(200, 140, 255),
+    ),
+    (
+        "Flags Captured",
+        "",
+        |s| s.flags_captured,
+        Color32::from_rgb(255, 230, 90),
+    ),
+];
+
+pub fn toggle_dashboard(mut dashboard: ResMut<Dashboard>) {
+    dashboard.visible = !dashboard.visible;
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn record_metrics(
+    time: Res<Time>,
+    mut history: ResMut<MetricHistory>,
+    agents: Query<(&Transform, Option<&LinearVelocity>), With<Agent>>,
+    ghost_agents: Query<&Transform, (With<GhostAgent>, Without<Agent>)>,
+    mapping_error: Res<MappingError>,
+    tracker: Res<ExplorationTracker>,
+    stats: Res<PolicyStats>,
+    captures: Res<FlagCaptureCounts>,
+) {
+    let now = time.elapsed_secs();
+    let previous = history.samples.back().copied();
+    if previous.is_some_and(|sample| now - sample.time < SAMPLE_INTERVAL) {
+        return;
+    }
+    let Ok((agent, velocity)) = agents.single() else {
+        return;
+    };
+
+    let localization_error = ghost_agents.single().map_or(0.0, |ghost| {
+        agent.translation.xz().distance(ghost.translation.xz())
+    });
+    let speed = velocity.map_or(0.0, |velocity| velocity.0.xz().length());
+    // The mapping error and coverage come from the HUD and `track_coverage`, without the GIL.
+    let coverage = tracker
+        .coverage
+        .last()
+        .map_or(0.0, |&(_, fraction)| fraction);
+
+    let latest = &stats.latencies[history.latencies_seen.min(stats.latencies.len())..];
+    let policy_latency_ms = if latest.is_empty() {
+        previous.map_or(0.0, |sample| sample.policy_latency_ms)
+    } else {
+        let total = latest
+            .iter()
+            .map(|latency| latency.as_secs_f32())
+            .sum::<f32>();
+        total / latest.len() as f32 * 1000.0
+    };
+    history.latencies_seen = stats.latencies.len();
+
+    if history.samples.len() == MAX_SAMPLES {
+        history.samples.pop_front();
+    }
+    history.samples.push_back(MetricSample {
+        time: now,
+        localization_error,
+        mapping_error: mapping_error.fraction() * 100.0,
+        coverage: coverage * 100.0,
+        speed,
+        policy_latency_ms,
+        flags_captured: captures.0 as f32,
+    });
+}
+
+/// Draws one rolling plot, newest sample on the right, scaled from zero to its largest value.
+fn plot(ui: &mut egui::Ui, values: &[f32], color: Color32) {
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(PLOT_WIDTH, PLOT_HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, Color32::from_black_alpha(120));
+    if values.len() < 2 {
+        return;
+    }
+
+    let max = values.iter().copied().fold(f32::EPSILON, f32::max);
+    let step = rect.width() / (MAX_SAMPLES - 1) as f32;
+    let offset = MAX_SAMPLES - values.len();
+    let points = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            egui::pos2(
+                rect.left() + (offset + i) as f32 * step,
+                rect.bottom() - value / max * rect.height(),
+            )
+        })
+        .collect();
+    painter.add(egui::Shape::line(points, Stroke::new(1.5, color)));
+    painter.text(
+        rect.right_top(),
+        egui::Align2::RIGHT_TOP,
+        format!("{max:.2}"),
+        egui::FontId::monospace(10.0),
+        Color32::GRAY,
+    );
+}
+
+pub fn draw_dashboard(
+    mut contexts: EguiContexts,
+    dashboard: Res<Dashboard>,
+    history: Res<MetricHistory>,
+) -> Result {
+    if !dashboard.visible {
+        return Ok(());
+    }
+
+    egui::Window::new("Metrics")
+        .default_pos([10.0, 160.0])
+        .resizable(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            for (name, unit, value, color) in PLOTS {
+                let values = history.samples.iter().map(value).collect::<Vec<_>>();
+                let latest = values.last().copied().unwrap_or(0.0);
+                ui.label(format!("{name}: {latest:.2}{unit}"));
+                plot(ui, &values, color);
+            }
+        });
+    Ok(())
+}
@@ -0,0 +1,257 @@
+//! Shapes that a Python policy draws over the scene to show its internals: planned paths,
+//! particles, frontier targets and so on.
+//!
+//! The simulator sets a `DebugDraw` as the policy's `debug_draw` attribute. What the policy draws
+//! during one `get_action` call is shown until it returns from the next one. In the window the
+//! shapes are drawn with gizmos; with `MazeConfig.recording` they are also drawn into the
+//! recorded frames, except for text labels.
+
+mod systems;
+
+use bevy::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+
+use crate::core::MazeConfig;
+use crate::occupancy_grid::OccupancyGrid;
+
+const DEFAULT_COLOR: Srgba = Srgba::rgb(1.0, 1.0, 0.0);
+
+/// Something to draw, in world (x, y) coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugShape {
+    Line {
+        start: Vec2,
+        end: Vec2,
+        color: Srgba,
+    },
+    Point {
+        position: Vec2,
+        size: f32,
+        color: Srgba,
+    },
+    Circle {
+        center: Vec2,
+        radius: f32,
+        color: Srgba,
+    },
+    Text {
+        position: Vec2,
+        text: String,
+        color: Srgba,
+    },
+    /// One RGBA pixel per cell of the player grid, row by row.
+    Heatmap {
+        origin: Vec2,
+        cell_size: f32,
+        width: usize,
+        height: usize,
+        pixels: Vec<u8>,
+    },
+}
+
+/// The shapes from the policy's latest `get_action` call.
+#[derive(Resource, Default)]
+pub struct DebugShapes {
+    pub shapes: Vec<DebugShape>,
+}
+
+/// Queues shapes to draw over the scene, in world coordinates. Colors are (r, g, b) or
+/// (r, g, b, a) tuples of floats between 0 and 1, yellow by default.
+#[gen_stub_pyclass]
+#[pyclass(name = "DebugDraw")]
+pub struct DebugDraw {
+    shapes: Vec<DebugShape>,
+    /// The layout of the player grid, which heatmaps follow.
+    origin: Vec2,
+    cell_size: f32,
+    width: usize,
+    height: usize,
+}
+
+impl DebugDraw {
+    pub fn new(grid: &OccupancyGrid) -> Self {
+        Self {
+            shapes: Vec::new(),
+            origin: grid.origin.into(),
+            cell_size: grid.cell_size,
+            width: grid.width,
+            height: grid.height,
+        }
+    }
+
+    /// Hands over everything drawn since the last call.
+    pub fn take(&mut self) -> Vec<DebugShape> {
+        std::mem::take(&mut self.shapes)
+    }
+}
+
+fn to_color(color: Option<Vec<f32>>) -> PyResult<Srgba> {
+    match color.as_deref() {
+        None => Ok(DEFAULT_COLOR),
+        Some(&[r, g, b]) => Ok(Srgba::rgb(r, g, b)),
+        Some(&[r, g, b, a]) => Ok(Srgba::new(r, g, b, a)),
+        Some(other) => Err(PyValueError::new_err(format!(
+            "A color needs 3 or 4 components, got {}",
+            other.len()
+        ))),
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl DebugDraw {
+    #[pyo3(signature = (start, end, color=None))]
+    fn line(
+        &mut self,
+        start: (f32, f32),
+        end: (f32, f32),
+        color: Option<Vec<f32>>,
+    ) -> PyResult<()> {
+        self.shapes.push(DebugShape::Line {
+            start: start.into(),
+            end: end.into(),
+            color: to_color(color)?,
+        });
+        Ok(())
+    }
+
+    /// Lines joining consecutive points, such as a planned path.
+    #[pyo3(signature = (points, color=None))]
+    fn path(&mut self, points: Vec<(f32, f32)>, color: Option<Vec<f32>>) -> PyResult<()> {
+        let color = to_color(color)?;
+        for pair in points.windows(2) {
+            self.shapes.push(DebugShape::Line {
+                start: pair[0].into(),
+                end: pair[1].into(),
+                color,
+            });
+        }
+        Ok(())
+    }
+
+    /// A filled dot `size` world units across.
+    #[pyo3(signature = (position, color=None, size=0.3))]
+    fn point(&mut self, position: (f32, f32), color: Option<Vec<f32>>, size: f32) -> PyResult<()> {
+        self.shapes.push(DebugShape::Point {
+            position: position.into(),
+            size,
+            color: to_color(color)?,
+        });
+        Ok(())
+    }
+
+    /// Draws many points at once, such as particles.
+    #[pyo3(signature = (positions, color=None, size=0.3))]
+    fn points(
+        &mut self,
+        positions: Vec<(f32, f32)>,
+        color: Option<Vec<f32>>,
+        size: f32,
+    ) -> PyResult<()> {
+        let color = to_color(color)?;
+        self.shapes
+            .extend(positions.into_iter().map(|position| DebugShape::Point {
+                position: position.into(),
+                size,
+                color,
+            }));
+        Ok(())
+    }
+
+    /// The outline of a circle.
+    #[pyo3(signature = (center, radius, color=None))]
+    fn circle(&mut self, center: (f32, f32), radius: f32, color: Option<Vec<f32>>) -> PyResult<()> {
+        self.shapes.push(DebugShape::Circle {
+            center: center.into(),
+            radius,
+            color: to_color(color)?,
+        });
+        Ok(())
+    }
+
+    /// A text label anchored at `position`. Labels are only shown in the window.
+    #[pyo3(signature = (position, text, color=None))]
+    fn text(
+        &mut self,
+        position: (f32, f32),
+        text: String,
+        color: Option<Vec<f32>>,
+    ) -> PyResult<()> {
+        self.shapes.push(DebugShape::Text {
+            position: position.into(),
+            text,
+            color: to_color(color)?,
+        });
+        Ok(())
+    }
+
+    /// Colors every cell of the player grid by a value, indexed [x][y] like
+    /// `OccupancyGrid.cost_map`. Values are scaled from `min` (blue) to `max` (red), which
+    /// default to the smallest and largest finite values. NaN and infinite cells are left clear.
+    #[pyo3(signature = (values, min=None, max=None, alpha=0.5))]
+    fn heatmap(
+        &mut self,
+        values: Vec<Vec<f32>>,
+        min: Option<f32>,
+        max: Option<f32>,
+        alpha: f32,
+    ) -> PyResult<()> {
+        if values.len() != self.width || values.iter().any(|column| column.len() != self.height) {
+            return Err(PyValueError::new_err(format!(
+                "A heatmap must have the shape of the grid, {}x{}",
+                self.width, self.height
+            )));
+        }
+
+        let finite = || values.iter().flatten().copied().filter(|v| v.is_finite());
+        let min = min.unwrap_or_else(|| finite().fold(f32::INFINITY, f32::min));
+        let max = max.unwrap_or_else(|| finite().fold(f32::NEG_INFINITY, f32::max));
+        let range = (max - min).max(f32::EPSILON);
+
+        let mut pixels = vec![0; self.width * self.height * 4];
+        for (x, column) in values.iter().enumerate() {
+            for (y, &value) in column.iter().enumerate() {
+                if !value.is_finite() {
+                    continue;
+                }
+                let t = ((value - min) / range).clamp(0.0, 1.0);
+                let color = Srgba::new(t, 0.2 * (1.0 - (2.0 * t - 1.0).abs()), 1.0 - t, alpha);
+                let offset = (x + y * self.width) * 4;
+                pixels[offset..offset + 4].copy_from_slice(&color.to_u8_array());
+            }
+        }
+
+        self.shapes.push(DebugShape::Heatmap {
+            origin: self.origin,
+            cell_size: self.cell_size,
+            width: self.width,
+            height: self.height,
+            pixels,
+        });
+        Ok(())
+    }
+
+    /// Drops everything drawn so far in this call.
+    fn clear(&mut self) {
+        self.shapes.clear();
+    }
+}
+
+pub struct DebugDrawPlugin;
+impl Plugin for DebugDrawPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugShapes>();
+        app.add_systems(
+            Update,
+            (
+                systems::draw_debug_shapes,
+                systems::sync_debug_entities,
+                systems::position_debug_labels,
+            )
+                .chain()
+                .run_if(|config: Res<MazeConfig>| !config.headless),
+        );
+    }
+}
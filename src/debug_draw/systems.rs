@@ -0,0 +1,165 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::camera::MainCamera;
+use crate::debug_draw::{DebugShape, DebugShapes};
+use crate::scene::WALL_HEIGHT;
+
+/// Heights above the ground at which shapes are drawn, just over the walls and grid overlays.
+const HEATMAP_HEIGHT: f32 = WALL_HEIGHT + 0.01;
+const SHAPE_HEIGHT: f32 = WALL_HEIGHT + 0.02;
+
+/// A heatmap plane or text label spawned for the current shapes.
+#[derive(Component)]
+pub struct DebugEntity;
+
+/// A text label that follows a world position on screen.
+#[derive(Component)]
+pub struct DebugLabel(Vec2);
+
+fn to_world(position: Vec2, height: f32) -> Vec3 {
+    Vec3::new(position.x, height, position.y)
+}
+
+pub fn draw_debug_shapes(shapes: Res<DebugShapes>, mut gizmos: Gizmos) {
+    // Circles are drawn in the xy plane unless rotated onto the ground.
+    let flat = Quat::from_rotation_x(FRAC_PI_2);
+    for shape in &shapes.shapes {
+        match *shape {
+            DebugShape::Line { start, end, color } => {
+                gizmos.line(
+                    to_world(start, SHAPE_HEIGHT),
+                    to_world(end, SHAPE_HEIGHT),
+                    color,
+                );
+            }
+            DebugShape::Point {
+                position,
+                size,
+                color,
+            } => {
+                gizmos.sphere(
+                    Isometry3d::from_translation(to_world(position, SHAPE_HEIGHT)),
+                    size * 0.5,
+                    color,
+                );
+            }
+            DebugShape::Circle {
+                center,
+                radius,
+                color,
+            } => {
+                gizmos.circle(
+                    Isometry3d::new(to_world(center, SHAPE_HEIGHT), flat),
+                    radius,
+                    color,
+                );
+            }
+            DebugShape::Text { .. } | DebugShape::Heatmap { .. } => {}
+        }
+    }
+}
+
+/// Replaces the heatmap planes and text labels whenever the policy draws a new set of shapes.
+pub fn sync_debug_entities(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    shapes: Res<DebugShapes>,
+    existing: Query<Entity, With<DebugEntity>>,
+) {
+    if !shapes.is_changed() {
+        return;
+    }
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+
+    for shape in &shapes.shapes {
+        match shape {
+            DebugShape::Heatmap {
+                origin,
+                cell_size,
+                width,
+                height,
+                pixels,
+            } => {
+                let mut image = Image::new(
+                    Extent3d {
+                        width: *width as u32,
+                        height: *height as u32,
+                        depth_or_array_layers: 1,
+                    },
+                    TextureDimension::D2,
+                    pixels.clone(),
+                    TextureFormat::Rgba8UnormSrgb,
+                    RenderAssetUsages::all(),
+                );
+                image.sampler = ImageSampler::nearest();
+
+                let size = Vec2::new(*width as f32, *height as f32) * *cell_size;
+                commands.spawn((
+                    Mesh3d(meshes.add(Plane3d::default().mesh().size(size.x, size.y))),
+                    MeshMaterial3d(materials.add(StandardMaterial {
+                        base_color_texture: Some(images.add(image)),
+                        unlit: true,
+                        alpha_mode: AlphaMode::Blend,
+                        ..default()
+                    })),
+                    Transform::from_translation(to_world(origin + size * 0.5, HEATMAP_HEIGHT)),
+                    DebugEntity,
+                    Name::new("DebugHeatmap"),
+                ));
+            }
+            DebugShape::Text {
+                position,
+                text,
+                color,
+            } => {
+                commands.spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    Text::new(text.clone()),
+                    TextFont {
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    TextColor((*color).into()),
+                    // Hidden until placed, so labels do not flash in the corner.
+                    Visibility::Hidden,
+                    DebugLabel(*position),
+                    DebugEntity,
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn position_debug_labels(
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut labels: Query<(&DebugLabel, &mut Node, &mut Visibility)>,
+) {
+    let Ok((camera, camera_transform)) = camera.single() else {
+        return;
+    };
+    for (label, mut node, mut visibility) in &mut labels {
+        match camera.world_to_viewport(camera_transform, to_world(label.0, SHAPE_HEIGHT)) {
+            Ok(position) => {
+                node.left = Val::Px(position.x);
+                node.top = Val::Px(position.y);
+                *visibility = Visibility::Inherited;
+            }
+            Err(_) => *visibility = Visibility::Hidden,
+        }
+    }
+}
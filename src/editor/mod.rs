@@ -0,0 +1,94 @@
+//! An editor for the scene in the window. Press M to pause the simulation and edit: clicks add
+//! and remove walls, place, drag and remove flags and capture points, and move the agent. The
+//! true grid follows every change, and X exports the scene as a `MazeLayout` that
+//! `MazeGenerationConfig.layout` can load.
+
+mod systems;
+
+use bevy::input::common_conditions::input_just_pressed;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::input::egui_wants_any_pointer_input;
+
+use crate::core::MazeConfig;
+
+pub const TOGGLE_EDITOR_KEY: KeyCode = KeyCode::KeyM;
+pub const EXPORT_KEY: KeyCode = KeyCode::KeyX;
+/// How close to a flag or capture point a click must be to pick it.
+pub const PICK_RADIUS: f32 = 1.5;
+/// Where the layout is exported when `maze_generation.layout` is not set.
+pub const DEFAULT_LAYOUT_PATH: &str = "layout.yaml";
+
+/// What clicks in the editor change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EditorTool {
+    /// Click two maze cell corners to add a wall between them; right click removes a wall.
+    #[default]
+    Wall,
+    /// Click to place a flag or drag one; right click removes one.
+    Flag,
+    /// Click to place a capture point or drag one; right click removes one.
+    CapturePoint,
+    /// Click to move the agent and its spawn.
+    AgentSpawn,
+}
+
+impl EditorTool {
+    pub fn label(self) -> &'static str {
+        match self {
+            EditorTool::Wall => "Wall",
+            EditorTool::Flag => "Flag",
+            EditorTool::CapturePoint => "Capture Point",
+            EditorTool::AgentSpawn => "Agent Spawn",
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Editor {
+    pub enabled: bool,
+    pub tool: EditorTool,
+    /// The first corner of the wall being added.
+    pub wall_start: Option<Vec2>,
+    /// The flag or capture point following the cursor while the mouse button is held.
+    pub dragging: Option<Entity>,
+    /// Whether the simulation was already paused when the editor opened, so that closing it
+    /// leaves it paused.
+    pub was_paused: bool,
+}
+
+fn editor_enabled(editor: Res<Editor>) -> bool {
+    editor.enabled
+}
+
+pub struct EditorPlugin;
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        if app.world().resource::<MazeConfig>().headless {
+            return;
+        }
+
+        app.init_resource::<Editor>();
+        app.add_systems(Startup, systems::setup_editor_text);
+        app.add_systems(
+            Update,
+            (
+                systems::toggle_editor.run_if(input_just_pressed(TOGGLE_EDITOR_KEY)),
+                (
+                    systems::select_tool,
+                    (
+                        systems::edit_walls,
+                        systems::edit_flags,
+                        systems::edit_capture_points,
+                        systems::move_agent_spawn,
+                    )
+                        .run_if(not(egui_wants_any_pointer_input)),
+                    systems::export_layout.run_if(input_just_pressed(EXPORT_KEY)),
+                    systems::draw_editor,
+                )
+                    .run_if(editor_enabled),
+                systems::update_editor_text,
+            )
+                .chain(),
+        );
+    }
+}
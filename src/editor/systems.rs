@@ -0,0 +1,414 @@
+use std::f32::consts::FRAC_PI_2;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::agent::{Agent, AgentSpawn, GhostAgent};
+use crate::core::MazeConfig;
+use crate::editor::{DEFAULT_LAYOUT_PATH, Editor, EditorTool, PICK_RADIUS};
+use crate::flag::{
+    CapturePoint, CapturePointGraphicsAssets, Flag, FlagGraphicsAssets, FlagStatus,
+    spawn_capture_point, spawn_flag,
+};
+use crate::occupancy_grid::HoverCell;
+use crate::scene::{MazeLayout, WALL_HEIGHT, WALL_THICKNESS, WallSegments};
+
+const GIZMO_HEIGHT: f32 = WALL_HEIGHT + 0.02;
+const GIZMO_COLOR: Srgba = Srgba::rgb(1.0, 0.5, 0.0);
+
+#[derive(Component)]
+pub struct EditorText;
+
+/// The corners of the maze cells, which walls are snapped to.
+struct CornerGrid {
+    origin: Vec2,
+    cell_size: f32,
+    cells: UVec2,
+}
+
+impl CornerGrid {
+    fn new(config: &MazeConfig) -> Self {
+        let generation = &config.maze_generation;
+        Self {
+            origin: generation.origin(),
+            cell_size: generation.cell_size,
+            cells: generation.cells(),
+        }
+    }
+
+    fn max(&self) -> Vec2 {
+        self.origin + self.cells.as_vec2() * self.cell_size
+    }
+
+    fn snap(&self, position: Vec2) -> Vec2 {
+        let index = ((position - self.origin) / self.cell_size)
+            .round()
+            .clamp(Vec2::ZERO, self.cells.as_vec2());
+        self.origin + index * self.cell_size
+    }
+
+    /// The corner nearest `position` on the same row or column as `start`.
+    fn snap_aligned(&self, start: Vec2, position: Vec2) -> Vec2 {
+        let end = self.snap(position);
+        let delta = end - start;
+        if delta.x.abs() >= delta.y.abs() {
+            Vec2::new(end.x, start.y)
+        } else {
+            Vec2::new(start.x, end.y)
+        }
+    }
+}
+
+fn cursor(hover: &HoverCell) -> Option<Vec2> {
+    hover.world_hit.map(|hit| hit.xz())
+}
+
+fn to_world(position: Vec2) -> Vec3 {
+    Vec3::new(position.x, GIZMO_HEIGHT, position.y)
+}
+
+fn distance_to_segment(point: Vec2, (p0, p1): (Vec2, Vec2)) -> f32 {
+    let along = p1 - p0;
+    let t = ((point - p0).dot(along) / along.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    point.distance(p0 + along * t)
+}
+
+/// The entity nearest `cursor` within `PICK_RADIUS`.
+fn pick(cursor: Vec2, candidates: impl Iterator<Item = (Entity, Vec3)>) -> Option<Entity> {
+    candidates
+        .map(|(entity, position)| (entity, position.xz().distance(cursor)))
+        .filter(|&(_, distance)| distance <= PICK_RADIUS)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+pub fn setup_editor_text(mut commands: Commands) {
+    commands.spawn((
+        EditorText,
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(5.0),
+            left: Val::Px(5.0),
+            padding: Val::Px(2.5).into(),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Visibility::Hidden,
+    ));
+}
+
+pub fn update_editor_text(
+    editor: Res<Editor>,
+    mut text: Query<(&mut Text, &mut Visibility), With<EditorText>>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = text.single_mut() else {
+        return;
+    };
+
+    *visibility = if editor.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    let hint = match (editor.tool, editor.wall_start) {
+        (EditorTool::Wall, Some(_)) => "Click the other end of the wall, right click to cancel",
+        (EditorTool::Wall, None) => {
+            "Click a corner to start a wall, right click a wall to remove it"
+        }
+        (EditorTool::AgentSpawn, _) => "Click to move the agent",
+        _ => "Click to place or drag, right click to remove",
+    };
+    text.0 = format!(
+        "Editing: {} | {}\n1: Wall | 2: Flag | 3: Capture Point | 4: Agent Spawn | X: Export | M: Resume",
+        editor.tool.label(),
+        hint
+    );
+}
+
+pub fn toggle_editor(mut editor: ResMut<Editor>, mut time: ResMut<Time<Physics>>) {
+    editor.enabled = !editor.enabled;
+    editor.wall_start = None;
+    editor.dragging = None;
+
+    if editor.enabled {
+        editor.was_paused = time.is_paused();
+        time.pause();
+    } else if !editor.was_paused {
+        time.unpause();
+    }
+}
+
+pub fn select_tool(keys: Res<ButtonInput<KeyCode>>, mut editor: ResMut<Editor>) {
+    let tool = [
+        (KeyCode::Digit1, EditorTool::Wall),
+        (KeyCode::Digit2, EditorTool::Flag),
+        (KeyCode::Digit3, EditorTool::CapturePoint),
+        (KeyCode::Digit4, EditorTool::AgentSpawn),
+    ]
+    .into_iter()
+    .find(|&(key, _)| keys.just_pressed(key));
+
+    if let Some((_, tool)) = tool {
+        editor.tool = tool;
+        editor.wall_start = None;
+        editor.dragging = None;
+    }
+}
+
+pub fn edit_walls(
+    buttons: Res<ButtonInput<MouseButton>>,
+    hover: Res<HoverCell>,
+    config: Res<MazeConfig>,
+    mut editor: ResMut<Editor>,
+    mut segments: ResMut<WallSegments>,
+) {
+    if editor.tool != EditorTool::Wall {
+        return;
+    }
+    let corners = CornerGrid::new(&config);
+
+    if buttons.just_pressed(MouseButton::Right) {
+        if editor.wall_start.take().is_some() {
+            return;
+        }
+        let Some(cursor) = cursor(&hover) else {
+            return;
+        };
+        let nearest = segments
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, &segment)| (i, distance_to_segment(cursor, segment)))
+            .filter(|&(_, distance)| distance <= corners.cell_size * 0.5)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, _)) = nearest {
+            segments.0.remove(i);
+        }
+        return;
+    }
+
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = cursor(&hover) else {
+        return;
+    };
+    let Some(start) = editor.wall_start else {
+        editor.wall_start = Some(corners.snap(cursor));
+        return;
+    };
+    let end = corners.snap_aligned(start, cursor);
+    if end == start {
+        return;
+    }
+
+    // Like generated walls, extend past both corners by half a thickness so that walls meeting
+    // at a corner close it, without leaving the maze.
+    let pad = (end - start).normalize() * WALL_THICKNESS * 0.5;
+    let p0 = (start - pad).clamp(corners.origin, corners.max());
+    let p1 = (end + pad).clamp(corners.origin, corners.max());
+    segments.0.push((p0, p1));
+    editor.wall_start = None;
+}
+
+pub fn edit_flags(
+    mut commands: Commands,
+    buttons: Res<ButtonInput<MouseButton>>,
+    hover: Res<HoverCell>,
+    mut editor: ResMut<Editor>,
+    graphics: Option<Res<FlagGraphicsAssets>>,
+    mut flags: Query<(Entity, &Flag, &mut Transform)>,
+) {
+    if editor.tool != EditorTool::Flag {
+        return;
+    }
+    if buttons.just_released(MouseButton::Left) {
+        editor.dragging = None;
+    }
+    let Some(cursor) = cursor(&hover) else {
+        return;
+    };
+    // Flags that are carried or captured belong to the game.
+    let dropped = flags
+        .iter()
+        .filter(|(_, flag, _)| flag.status == FlagStatus::Dropped)
+        .map(|(entity, _, transform)| (entity, transform.translation));
+
+    if buttons.just_pressed(MouseButton::Right) {
+        if let Some(entity) = pick(cursor, dropped) {
+            commands.entity(entity).despawn();
+        }
+    } else if buttons.just_pressed(MouseButton::Left) {
+        editor.dragging = pick(cursor, dropped);
+        if editor.dragging.is_none() {
+            let number = flags.iter().count() + 1;
+            spawn_flag(&mut commands, graphics.as_deref(), number, cursor);
+        }
+    } else if let Some((_, _, mut transform)) = editor
+        .dragging
+        .and_then(|entity| flags.get_mut(entity).ok())
+    {
+        transform.translation.x = cursor.x;
+        transform.translation.z = cursor.y;
+    }
+}
+
+pub fn edit_capture_points(
+    mut commands: Commands,
+    buttons: Res<ButtonInput<MouseButton>>,
+    hover: Res<HoverCell>,
+    mut editor: ResMut<Editor>,
+    graphics: Option<Res<CapturePointGraphicsAssets>>,
+    mut capture_points: Query<(Entity, &mut Transform), With<CapturePoint>>,
+) {
+    if editor.tool != EditorTool::CapturePoint {
+        return;
+    }
+    if buttons.just_released(MouseButton::Left) {
+        editor.dragging = None;
+    }
+    let Some(cursor) = cursor(&hover) else {
+        return;
+    };
+    let all = capture_points
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation));
+
+    if buttons.just_pressed(MouseButton::Right) {
+        if let Some(entity) = pick(cursor, all) {
+            commands.entity(entity).despawn();
+        }
+    } else if buttons.just_pressed(MouseButton::Left) {
+        editor.dragging = pick(cursor, all);
+        if editor.dragging.is_none() {
+            let number = capture_points.iter().count() + 1;
+            spawn_capture_point(&mut commands, graphics.as_deref(), number, cursor);
+        }
+    } else if let Some((_, mut transform)) = editor
+        .dragging
+        .and_then(|entity| capture_points.get_mut(entity).ok())
+    {
+        transform.translation.x = cursor.x;
+        transform.translation.z = cursor.y;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn move_agent_spawn(
+    buttons: Res<ButtonInput<MouseButton>>,
+    hover: Res<HoverCell>,
+    editor: Res<Editor>,
+    mut spawn: ResMut<AgentSpawn>,
+    mut agents: Query<
+        (
+            &mut Transform,
+            Option<&mut Position>,
+            Option<&mut LinearVelocity>,
+        ),
+        (With<Agent>, Without<GhostAgent>),
+    >,
+    mut ghost_agents: Query<&mut Transform, (With<GhostAgent>, Without<Agent>)>,
+) {
+    if editor.tool != EditorTool::AgentSpawn || !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = cursor(&hover) else {
+        return;
+    };
+    spawn.0 = cursor;
+
+    // The physics position is written back to the transform every step, so both are moved.
+    for (mut transform, position, velocity) in &mut agents {
+        transform.translation.x = cursor.x;
+        transform.translation.z = cursor.y;
+        if let Some(mut position) = position {
+            position.0 = transform.translation;
+        }
+        if let Some(mut velocity) = velocity {
+            velocity.0 = Vec3::ZERO;
+        }
+    }
+    for mut transform in &mut ghost_agents {
+        transform.translation.x = cursor.x;
+        transform.translation.z = cursor.y;
+    }
+}
+
+pub fn export_layout(
+    config: Res<MazeConfig>,
+    segments: Res<WallSegments>,
+    flags: Query<&GlobalTransform, With<Flag>>,
+    capture_points: Query<&GlobalTransform, With<CapturePoint>>,
+    spawn: Res<AgentSpawn>,
+) {
+    let layout = MazeLayout {
+        width: config.maze_generation.width,
+        height: config.maze_generation.height,
+        walls: segments
+            .0
+            .iter()
+            .map(|&(p0, p1)| (p0.into(), p1.into()))
+            .collect(),
+        flags: flags.iter().map(|t| t.translation().xz().into()).collect(),
+        capture_points: capture_points
+            .iter()
+            .map(|t| t.translation().xz().into())
+            .collect(),
+        agent_spawn: Some(spawn.0.into()),
+    };
+
+    let path = config
+        .maze_generation
+        .layout
+        .as_deref()
+        .unwrap_or(DEFAULT_LAYOUT_PATH);
+    match layout.save(path) {
+        Ok(()) => info!("Exported the maze layout to {}", path),
+        Err(e) => error!("{:#}", e),
+    }
+}
+
+pub fn draw_editor(
+    editor: Res<Editor>,
+    hover: Res<HoverCell>,
+    config: Res<MazeConfig>,
+    mut gizmos: Gizmos,
+) {
+    let Some(cursor) = cursor(&hover) else {
+        return;
+    };
+    // Circles are drawn in the xy plane unless rotated onto the ground.
+    let flat = Quat::from_rotation_x(FRAC_PI_2);
+
+    if editor.tool != EditorTool::Wall {
+        gizmos.circle(
+            Isometry3d::new(to_world(cursor), flat),
+            PICK_RADIUS,
+            GIZMO_COLOR,
+        );
+        return;
+    }
+
+    let corners = CornerGrid::new(&config);
+    let corner = match editor.wall_start {
+        Some(start) => {
+            let end = corners.snap_aligned(start, cursor);
+            gizmos.line(to_world(start), to_world(end), GIZMO_COLOR);
+            end
+        }
+        None => corners.snap(cursor),
+    };
+    gizmos.sphere(
+        Isometry3d::from_translation(to_world(corner)),
+        WALL_THICKNESS * 0.5,
+        GIZMO_COLOR,
+    );
+}
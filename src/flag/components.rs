@@ -1,5 +1,8 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::gen_stub_pyclass_enum;
+use serde::Serialize;
 
 use crate::flag::{
     CAPTURE_POINT_INTERACTION_RADIUS, COLLISION_LAYER_CAPTURE_POINT, COLLISION_LAYER_FLAG,
@@ -8,7 +11,11 @@ use crate::flag::{
 use crate::interaction_range::{InteractionRadius, VisibleRange};
 use crate::scene::COLLISION_LAYER_WALL;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+/// Where a flag is in its life cycle.
+#[gen_stub_pyclass_enum]
+#[pyclass(name = "FlagStatus", frozen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FlagStatus {
     Dropped,
     PickedUp,
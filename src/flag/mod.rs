@@ -9,6 +9,8 @@ use pyo3_stub_gen::derive::gen_stub_pyclass;
 use serde::{Deserialize, Serialize};
 
 pub use components::*;
+pub use systems::{spawn_capture_point, spawn_flag};
+pub use visual::{CapturePointGraphicsAssets, FlagGraphicsAssets};
 
 use crate::core::{MazeConfig, StartupSets};
 
@@ -84,10 +86,7 @@ impl Plugin for FlagPlugin {
                 .in_set(StartupSets::FlagsAndCapturePoints),
         );
 
-        app.add_systems(
-            Update,
-            systems::update_true_grid.run_if(|config: Res<MazeConfig>| !config.headless),
-        );
+        app.add_systems(Update, systems::update_true_grid);
     }
 }
 
@@ -7,7 +7,7 @@ use crate::core::MazeConfig;
 use crate::flag::{CapturePoint, CapturePointBundle, FLAG_INTERACTION_RADIUS, Flag};
 use crate::occupancy_grid::{LOGIT_CLAMP, OccupancyGrid, TrueGrid};
 use crate::python::game_state::EntityType;
-use crate::scene::{WALL_THICKNESS, WallSegments};
+use crate::scene::{MazeLayout, WALL_THICKNESS, WallSegments};
 
 use super::components::FlagBundle;
 use super::visual::{CapturePointGraphicsAssets, FlagGraphicsAssets};
@@ -60,12 +60,6 @@ fn mark_neighborhood_units(
     }
 }
 
-fn grid_to_world_xy(col: u32, row: u32, cell_size: f32, world_w: f32, world_h: f32) -> (f32, f32) {
-    let x = (col as f32) * cell_size + cell_size * 0.5 - world_w * 0.5;
-    let y = (row as f32) * cell_size + cell_size * 0.5 - world_h * 0.5;
-    (x, y)
-}
-
 /// Picks up to `count` indices for `place_as`, respecting 3.0-unit clearance from:
 /// - walls
 /// - existing flags/capture points
@@ -136,49 +130,12 @@ fn pick_positions_for(
     }
 
     // 5) Commit to grid and prepare world positions.
-    let world_w = config.maze_generation.width;
-    let world_h = config.maze_generation.height;
-
     let mut out = Vec::with_capacity(picked.len());
     for &i in &picked {
         py_grid.grid[i].assignment = Some(place_as);
-        let col = (i as u32) % (py_grid.width as u32);
-        let row = (i as u32) / (py_grid.width as u32);
-        out.push(grid_to_world_xy(col, row, cell_size, world_w, world_h));
-    }
-
-    out
-}
-
-fn overlapping_indexes(
-    aabb_min: Vec2,    // bottom-left  (x,y)
-    aabb_max: Vec2,    // top-right    (x,y)
-    cell_size: f32,    // occupancy grid cell size (world units)
-    world_width: f32,  // world extent in X
-    world_height: f32, // world extent in Y (your Z)
-) -> Vec<(u32, u32)> {
-    let grid_w = (world_width / cell_size).round() as i32;
-    let grid_h = (world_height / cell_size).round() as i32;
-
-    let half_w = world_width * 0.5;
-    let half_h = world_height * 0.5;
-
-    let min_c = (((aabb_min.x + half_w) / cell_size).floor() as i32).clamp(0, grid_w - 1);
-    let min_r = (((aabb_min.y + half_h) / cell_size).floor() as i32).clamp(0, grid_h - 1);
-
-    let max_c = ((((aabb_max.x + half_w) / cell_size).ceil() as i32) - 1).clamp(0, grid_w - 1);
-    let max_r = ((((aabb_max.y + half_h) / cell_size).ceil() as i32) - 1).clamp(0, grid_h - 1);
-
-    if max_c < min_c || max_r < min_r {
-        return Vec::new();
+        out.push(py_grid.cell_to_world((i % py_grid.width, i / py_grid.width)));
     }
 
-    let mut out = Vec::with_capacity(((max_c - min_c + 1) * (max_r - min_r + 1)) as usize);
-    for r in min_r..=max_r {
-        for c in min_c..=max_c {
-            out.push((c as u32, r as u32));
-        }
-    }
     out
 }
 
@@ -189,43 +146,64 @@ pub fn spawn_flags(
     flag_graphics: Option<Res<FlagGraphicsAssets>>,
     config: Res<MazeConfig>,
     true_grid: ResMut<TrueGrid>,
+    layout: Option<Res<MazeLayout>>,
 ) {
-    let positions = Python::attach(|py| {
-        let grid = true_grid.0.write().unwrap();
-        let mut py_obj = grid.borrow_mut(py);
-
-        let mut rng = ChaCha20Rng::from_seed({
-            let mut arr = [0u8; 32];
-            let seed = config
-                .maze_generation
-                .seed
-                .expect("Seed must be set before map generation");
-            arr[..4].copy_from_slice(&seed.to_le_bytes());
-            arr
-        });
+    let positions = match layout {
+        Some(layout) => layout.flags.clone(),
+        None => Python::attach(|py| {
+            let grid = true_grid.0.write().unwrap();
+            let mut py_obj = grid.borrow_mut(py);
 
-        pick_positions_for(
-            &mut py_obj,
-            &config,
-            &mut rng,
-            config.flags.number,
-            EntityType::Flag,
-        )
-    });
+            let mut rng = ChaCha20Rng::from_seed({
+                let mut arr = [0u8; 32];
+                let seed = config
+                    .maze_generation
+                    .seed
+                    .expect("Seed must be set before map generation");
+                arr[..4].copy_from_slice(&seed.to_le_bytes());
+                arr
+            });
+
+            pick_positions_for(
+                &mut py_obj,
+                &config,
+                &mut rng,
+                config.flags.number,
+                EntityType::Flag,
+            )
+        }),
+    };
 
     for (i, &(x, y)) in positions.iter().enumerate() {
-        let flag_name = format!("Flag {}", i + 1);
         info!("Spawning flag at position: ({x:.2}, {y:.2})");
+        spawn_flag(
+            &mut commands,
+            flag_graphics.as_deref(),
+            i + 1,
+            Vec2::new(x, y),
+        );
+    }
+}
 
-        let mut entity = commands.spawn(FlagBundle::new(&flag_name, Vec3::new(x, 0.5, y)));
-
-        if let Some(flag_graphics) = &flag_graphics {
-            entity.insert((
-                Mesh3d(flag_graphics.mesh.clone()),
-                MeshMaterial3d(flag_graphics.material.clone()),
-            ));
-        }
+/// Spawns the flag numbered `number`, counting from one, at a world (x, y) position.
+pub fn spawn_flag(
+    commands: &mut Commands,
+    graphics: Option<&FlagGraphicsAssets>,
+    number: usize,
+    position: Vec2,
+) -> Entity {
+    let name = format!("Flag {}", number);
+    let mut entity = commands.spawn(FlagBundle::new(
+        &name,
+        Vec3::new(position.x, 0.5, position.y),
+    ));
+    if let Some(graphics) = graphics {
+        entity.insert((
+            Mesh3d(graphics.mesh.clone()),
+            MeshMaterial3d(graphics.material.clone()),
+        ));
     }
+    entity.id()
 }
 
 pub fn spawn_capture_points(
@@ -233,51 +211,71 @@ pub fn spawn_capture_points(
     capture_point_graphics: Option<Res<CapturePointGraphicsAssets>>,
     config: Res<MazeConfig>,
     true_grid: ResMut<TrueGrid>,
+    layout: Option<Res<MazeLayout>>,
 ) {
-    let positions = Python::attach(|py| {
-        let grid = true_grid.0.write().unwrap();
-        let mut py_obj = grid.borrow_mut(py);
-
-        let mut rng = ChaCha20Rng::from_seed({
-            let mut arr = [0u8; 32];
-            let seed = config
-                .maze_generation
-                .seed
-                .expect("Seed must be set before map generation");
-            arr[..4].copy_from_slice(&seed.to_le_bytes());
-            arr
-        });
+    let positions = match layout {
+        Some(layout) => layout.capture_points.clone(),
+        None => Python::attach(|py| {
+            let grid = true_grid.0.write().unwrap();
+            let mut py_obj = grid.borrow_mut(py);
 
-        // Note: existing Flags are already in the grid now, so capture points will
-        // also keep 3u away from them.
-        pick_positions_for(
-            &mut py_obj,
-            &config,
-            &mut rng,
-            config.capture_points.number,
-            EntityType::CapturePoint,
-        )
-    });
+            let mut rng = ChaCha20Rng::from_seed({
+                let mut arr = [0u8; 32];
+                let seed = config
+                    .maze_generation
+                    .seed
+                    .expect("Seed must be set before map generation");
+                arr[..4].copy_from_slice(&seed.to_le_bytes());
+                arr
+            });
+
+            // Note: existing Flags are already in the grid now, so capture points will
+            // also keep 3u away from them.
+            pick_positions_for(
+                &mut py_obj,
+                &config,
+                &mut rng,
+                config.capture_points.number,
+                EntityType::CapturePoint,
+            )
+        }),
+    };
 
     for (i, &(x, y)) in positions.iter().enumerate() {
-        let name = format!("Capture Point {}", i + 1);
         info!("Spawning capture point at position: ({x:.2}, {y:.2})");
+        spawn_capture_point(
+            &mut commands,
+            capture_point_graphics.as_deref(),
+            i + 1,
+            Vec2::new(x, y),
+        );
+    }
+}
 
-        let mut entity = commands.spawn(CapturePointBundle::new(&name, Vec3::new(x, 0.5, y)));
-
-        if let Some(capture_point_graphics) = &capture_point_graphics {
-            entity.insert((
-                Mesh3d(capture_point_graphics.mesh.clone()),
-                MeshMaterial3d(capture_point_graphics.material.clone()),
-            ));
-        }
+/// Spawns the capture point numbered `number`, counting from one, at a world (x, y) position.
+pub fn spawn_capture_point(
+    commands: &mut Commands,
+    graphics: Option<&CapturePointGraphicsAssets>,
+    number: usize,
+    position: Vec2,
+) -> Entity {
+    let name = format!("Capture Point {}", number);
+    let mut entity = commands.spawn(CapturePointBundle::new(
+        &name,
+        Vec3::new(position.x, 0.5, position.y),
+    ));
+    if let Some(graphics) = graphics {
+        entity.insert((
+            Mesh3d(graphics.mesh.clone()),
+            MeshMaterial3d(graphics.material.clone()),
+        ));
     }
+    entity.id()
 }
 
 #[allow(clippy::type_complexity)]
 pub fn update_true_grid(
     true_grid: ResMut<TrueGrid>,
-    config: Res<MazeConfig>,
     segments: Res<WallSegments>,
     query_flag: Query<&GlobalTransform, With<Flag>>,
     query_cp: Query<&GlobalTransform, With<CapturePoint>>,
@@ -285,10 +283,12 @@ pub fn update_true_grid(
     Python::attach(|py| {
         let grid = true_grid.0.write().unwrap();
         let mut py_obj = grid.borrow_mut(py);
-        let width = py_obj.width as u32;
+        let width = py_obj.width;
 
+        // Walls are only redrawn, from scratch since the editor can remove them, when they change.
+        let walls_changed = segments.is_changed();
         for entry in &mut py_obj.grid {
-            if entry.assignment != Some(EntityType::Wall) {
+            if walls_changed || entry.assignment != Some(EntityType::Wall) {
                 entry.assignment = Some(EntityType::Empty);
                 entry.logit_free = LOGIT_CLAMP;
                 entry.logit_wall = -LOGIT_CLAMP;
@@ -296,6 +296,9 @@ pub fn update_true_grid(
                 entry.logit_capture_point = -LOGIT_CLAMP;
             }
         }
+        if !walls_changed {
+            return;
+        }
 
         for (p0, p1) in &segments.0 {
             let aabb_bottom_left = Vec2::new(
@@ -308,20 +311,14 @@ pub fn update_true_grid(
                 p0.y.max(p1.y) + WALL_THICKNESS * 0.5,
             );
 
-            let wall_indexes = overlapping_indexes(
-                aabb_bottom_left,
-                aabb_top_right,
-                config.agent.occupancy_grid_cell_size,
-                config.maze_generation.width,
-                config.maze_generation.height,
-            );
+            let wall_indexes = py_obj.cells_in_aabb(aabb_bottom_left.into(), aabb_top_right.into());
 
             for (ix, iy) in wall_indexes.iter().copied() {
-                py_obj.grid[(ix + iy * width) as usize].assignment = Some(EntityType::Wall);
-                py_obj.grid[(ix + iy * width) as usize].logit_free = -LOGIT_CLAMP;
-                py_obj.grid[(ix + iy * width) as usize].logit_wall = LOGIT_CLAMP;
-                py_obj.grid[(ix + iy * width) as usize].logit_flag = -LOGIT_CLAMP;
-                py_obj.grid[(ix + iy * width) as usize].logit_capture_point = -LOGIT_CLAMP;
+                py_obj.grid[ix + iy * width].assignment = Some(EntityType::Wall);
+                py_obj.grid[ix + iy * width].logit_free = -LOGIT_CLAMP;
+                py_obj.grid[ix + iy * width].logit_wall = LOGIT_CLAMP;
+                py_obj.grid[ix + iy * width].logit_flag = -LOGIT_CLAMP;
+                py_obj.grid[ix + iy * width].logit_capture_point = -LOGIT_CLAMP;
             }
         }
     });
@@ -335,20 +332,13 @@ pub fn update_true_grid(
             transform.translation().x + 0.5,
             transform.translation().z + 0.5,
         );
-        let overlapping = overlapping_indexes(
-            aabb_min,
-            aabb_max,
-            config.agent.occupancy_grid_cell_size,
-            config.maze_generation.width,
-            config.maze_generation.height,
-        );
 
         Python::attach(|py| {
             let grid = true_grid.0.write().unwrap();
             let mut py_obj = grid.borrow_mut(py);
 
-            for (col, row) in overlapping {
-                let idx = (row * (py_obj.width as u32) + col) as usize;
+            for (col, row) in py_obj.cells_in_aabb(aabb_min.into(), aabb_max.into()) {
+                let idx = row * py_obj.width + col;
                 py_obj.grid[idx].assignment = Some(EntityType::Flag);
                 py_obj.grid[idx].logit_free = -LOGIT_CLAMP;
                 py_obj.grid[idx].logit_wall = -LOGIT_CLAMP;
@@ -367,20 +357,13 @@ pub fn update_true_grid(
             transform.translation().x + 0.5,
             transform.translation().z + 0.5,
         );
-        let overlapping = overlapping_indexes(
-            aabb_min,
-            aabb_max,
-            config.agent.occupancy_grid_cell_size,
-            config.maze_generation.width,
-            config.maze_generation.height,
-        );
 
         Python::attach(|py| {
             let grid = true_grid.0.write().unwrap();
             let mut py_obj = grid.borrow_mut(py);
 
-            for (col, row) in overlapping {
-                let idx = (row * (py_obj.width as u32) + col) as usize;
+            for (col, row) in py_obj.cells_in_aabb(aabb_min.into(), aabb_max.into()) {
+                let idx = row * py_obj.width + col;
                 py_obj.grid[idx].assignment = Some(EntityType::CapturePoint);
                 py_obj.grid[idx].logit_free = -LOGIT_CLAMP;
                 py_obj.grid[idx].logit_wall = -LOGIT_CLAMP;
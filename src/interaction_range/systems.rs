@@ -1,9 +1,10 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
 
-use crate::agent::Agent;
+use crate::agent::{Action, ActionResultMessage, Agent};
 use crate::flag::{CapturePoint, Flag, FlagCaptureCounts, FlagStatus};
 use crate::interaction_range::messages::{FlagDropMessage, FlagPickupMessage};
+use crate::python::game_state::ActionResult;
 
 use super::components::{InteractionRadius, InteractionRange, VisibleRange};
 use super::visual::RingAssets;
@@ -72,13 +73,23 @@ pub fn remove_ring_on_radius_removal(
 pub fn handle_flag_pickups(
     mut commands: Commands,
     mut reader: MessageReader<FlagPickupMessage>,
+    mut results: MessageWriter<ActionResultMessage>,
     agents: Query<(Entity, &Transform, Option<&Children>), With<Agent>>,
     mut flags: Query<(Entity, &mut Flag, &mut Transform, &InteractionRadius), Without<Agent>>,
 ) {
-    for FlagPickupMessage { agent_id } in reader.read() {
-        let agent = agents.iter().find(|(e, _, _)| e.index() == *agent_id);
+    for &FlagPickupMessage { agent_id } in reader.read() {
+        let action = Action::PickupFlag { id: agent_id };
+        let mut reject = |reason: String| {
+            debug!("Rejected flag pickup: {}", reason);
+            results.write(ActionResultMessage {
+                agent_id,
+                result: ActionResult::rejected(action.clone(), reason),
+            });
+        };
+
+        let agent = agents.iter().find(|(e, _, _)| e.index() == agent_id);
         let Some((agent_entity, agent_transform, agent_children)) = agent else {
-            eprintln!("Agent with id {} either does not exist", agent_id);
+            reject(format!("agent with id {} does not exist", agent_id));
             continue;
         };
 
@@ -86,14 +97,15 @@ pub fn handle_flag_pickups(
         let carrying_flag = agent_children
             .is_some_and(|children| children.iter().any(|child| flags.get(child).is_ok()));
         if carrying_flag {
-            eprintln!(
-                "Agent with id {} is already carrying a flag and can not pick up another",
+            reject(format!(
+                "agent with id {} is already carrying a flag",
                 agent_id
-            );
+            ));
             continue;
         }
 
         let agent_position = agent_transform.translation.xz();
+        let mut picked_up = false;
         for (flag_entity, mut flag, mut flag_transform, InteractionRadius(radius)) in &mut flags {
             let flag_position = flag_transform.translation.xz();
             let distance = agent_position.distance(flag_position);
@@ -106,22 +118,43 @@ pub fn handle_flag_pickups(
                     .remove::<Collider>();
                 flag.status = FlagStatus::PickedUp;
                 flag_transform.translation = Vec3::new(0.0, 0.5, 0.0); // lift flag above agent
+                picked_up = true;
                 break;
             }
         }
+
+        if !picked_up {
+            reject(format!("no flag in range of agent with id {}", agent_id));
+            continue;
+        }
+
+        results.write(ActionResultMessage {
+            agent_id,
+            result: ActionResult::accepted(action),
+        });
     }
 }
 
 pub fn handle_flag_drop(
     mut commands: Commands,
     mut reader: MessageReader<FlagDropMessage>,
+    mut results: MessageWriter<ActionResultMessage>,
     agents: Query<(Entity, &Transform, Option<&Children>), With<Agent>>,
     mut flags: Query<(Entity, &mut Flag, &mut Transform), Without<Agent>>,
 ) {
-    for FlagDropMessage { agent_id } in reader.read() {
-        let agent = agents.iter().find(|(e, _, _)| e.index() == *agent_id);
+    for &FlagDropMessage { agent_id } in reader.read() {
+        let action = Action::DropFlag { id: agent_id };
+        let mut reject = |reason: String| {
+            debug!("Rejected flag drop: {}", reason);
+            results.write(ActionResultMessage {
+                agent_id,
+                result: ActionResult::rejected(action.clone(), reason),
+            });
+        };
+
+        let agent = agents.iter().find(|(e, _, _)| e.index() == agent_id);
         let Some((agent_entity, agent_transform, agent_children)) = agent else {
-            eprintln!("Agent with id {} either does not exist", agent_id);
+            reject(format!("agent with id {} does not exist", agent_id));
             continue;
         };
 
@@ -135,10 +168,7 @@ pub fn handle_flag_drop(
             })
         });
         let Some(flag_entity) = flag_entity else {
-            eprintln!(
-                "Agent with id {} is not carrying a flag and can not drop one",
-                agent_id
-            );
+            reject(format!("agent with id {} is not carrying a flag", agent_id));
             continue;
         };
 
@@ -150,6 +180,11 @@ pub fn handle_flag_drop(
             flag.status = FlagStatus::Dropped;
             flag_transform.translation = agent_transform.translation
         }
+
+        results.write(ActionResultMessage {
+            agent_id,
+            result: ActionResult::accepted(action),
+        });
     }
 }
 
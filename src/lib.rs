@@ -2,12 +2,19 @@ mod agent;
 mod camera;
 mod character_controller;
 mod core;
+mod dashboard;
 mod debug;
+mod debug_draw;
+mod editor;
 mod flag;
 mod interaction_range;
 mod occupancy_grid;
+mod policies;
 mod python;
+mod recording;
+mod reward;
 mod scene;
+mod teleop;
 
 use std::sync::{Arc, RwLock};
 
@@ -19,28 +26,36 @@ use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 use pyo3_stub_gen::{define_stub_info_gatherer, derive::gen_stub_pyfunction};
 
-use crate::core::MazeConfig;
+pub use crate::core::MazeConfig;
 use crate::occupancy_grid::OccupancyGrid;
 use crate::python::game_state::GameState;
-use crate::python::policy::{PythonPolicyBridgePlugin, TestHarnessBridge};
+use crate::python::policy::{PolicyErrorSlot, PythonPolicyBridgePlugin, TestHarnessBridge};
 use crate::python::state_queue::StateQueue;
+use crate::python::summary::EvaluationSummary;
 
-#[gen_stub_pyfunction]
-#[pyfunction(name = "parse_config")]
-fn parse_config(config_path: &str) -> PyResult<MazeConfig> {
+/// Reads a `MazeConfig` from a YAML file.
+pub fn load_config(config_path: &str) -> anyhow::Result<MazeConfig> {
     let config_str = std::fs::read_to_string(config_path)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to read config file: {}", e)))?;
+        .map_err(|e| anyhow::anyhow!("Failed to read config file: {}", e))?;
 
     let config: MazeConfig = serde_yaml::from_str(&config_str)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to parse config file: {}", e)))?;
+        .map_err(|e| anyhow::anyhow!("Failed to parse config file: {}", e))?;
 
     Ok(config)
 }
 
+#[gen_stub_pyfunction]
+#[pyfunction(name = "parse_config")]
+fn parse_config(config_path: &str) -> PyResult<MazeConfig> {
+    load_config(config_path).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
 fn generate_app(
     config: MazeConfig,
-    policy: Py<PyAny>,
+    layout: Option<scene::MazeLayout>,
+    policy: Option<Py<PyAny>>,
     test_harness: Option<TestHarnessBridge>,
+    policy_error: PolicyErrorSlot,
 ) -> App {
     let mut app = App::new();
     app.add_plugins((PhysicsPlugins::default(),));
@@ -55,17 +70,32 @@ fn generate_app(
         }));
 
         app.add_systems(Update, force_focus);
+    } else {
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            bevy::asset::AssetPlugin::default(),
+            bevy::mesh::MeshPlugin,
+            bevy::scene::ScenePlugin,
+            bevy::log::LogPlugin::default(),
+            bevy::input::InputPlugin,
+        ));
     }
 
     if config.debug {
         app.add_plugins(debug::DebugPlugin);
     }
 
+    if let Some(layout) = layout {
+        app.insert_resource(layout);
+    }
+
     app.add_plugins((
         PythonPolicyBridgePlugin {
             config: config.clone(),
             agent_policy: policy,
             test_harness,
+            policy_error,
         },
         core::MazePlugin {
             config: config.clone(),
@@ -77,13 +107,26 @@ fn generate_app(
 
 #[gen_stub_pyfunction]
 #[pyfunction(name = "run")]
-fn run(py: Python<'_>, config: MazeConfig, policy: Py<PyAny>) -> PyResult<Option<StateQueue>> {
+#[pyo3(signature = (config, policy=None))]
+fn run(
+    py: Python<'_>,
+    mut config: MazeConfig,
+    policy: Option<Py<PyAny>>,
+) -> PyResult<Option<StateQueue>> {
+    let policy_error = PolicyErrorSlot::default();
+    let layout =
+        scene::load_layout(&mut config).map_err(|e| PyRuntimeError::new_err(format!("{:#}", e)))?;
+
     if !config.headless {
         Python::detach(py, || {
-            let mut app = generate_app(config, policy, None);
+            let mut app = generate_app(config, layout, policy, None, policy_error.clone());
             app.run();
         });
-        Ok(None)
+
+        match policy_error.lock().unwrap().take() {
+            Some(err) => Err(err),
+            None => Ok(None),
+        }
     } else {
         let (tx_state, rx_state) = crossbeam_channel::bounded::<(
             GameState,
@@ -91,13 +134,21 @@ fn run(py: Python<'_>, config: MazeConfig, policy: Py<PyAny>) -> PyResult<Option
             Arc<RwLock<Py<OccupancyGrid>>>,
         )>(60);
         let (tx_stop, rx_stop) = crossbeam_channel::bounded::<()>(1);
+        let (tx_summary, rx_summary) = crossbeam_channel::bounded::<EvaluationSummary>(1);
 
         let rate_hz = config.agent.policy_hz;
+        let error = policy_error.clone();
         let join = std::thread::spawn(move || {
             let mut app = generate_app(
                 config,
+                layout,
                 policy,
-                Some(TestHarnessBridge { tx_state, rx_stop }),
+                Some(TestHarnessBridge {
+                    tx_state,
+                    rx_stop,
+                    tx_summary,
+                }),
+                policy_error,
             );
             app.run();
         });
@@ -105,12 +156,43 @@ fn run(py: Python<'_>, config: MazeConfig, policy: Py<PyAny>) -> PyResult<Option
         Ok(Some(StateQueue {
             rx_state,
             tx_stop,
+            rx_summary,
+            summary: None,
+            error,
             rate_hz,
             join: Some(join),
         }))
     }
 }
 
+/// Runs the simulator without a Python policy, for the `maze-sim` binary. The agent is driven by
+/// `agent.policy_server` if it is set, or by `agent.builtin_policy` otherwise. The run stops
+/// when the window is closed or, if given, after `duration` seconds.
+pub fn run_standalone(mut config: MazeConfig, duration: Option<f32>) -> anyhow::Result<()> {
+    Python::initialize();
+
+    let layout = scene::load_layout(&mut config)?;
+    let policy_error = PolicyErrorSlot::default();
+    let mut app = generate_app(config, layout, None, None, policy_error.clone());
+    if let Some(duration) = duration {
+        app.add_systems(
+            Update,
+            move |time: Res<Time>, mut exit: MessageWriter<AppExit>| {
+                if time.elapsed_secs() >= duration {
+                    exit.write(AppExit::Success);
+                }
+            },
+        );
+    }
+    let exit = app.run();
+
+    match policy_error.lock().unwrap().take() {
+        Some(err) => Err(anyhow::anyhow!("Policy failed: {}", err)),
+        None if exit.is_error() => Err(anyhow::anyhow!("Simulation stopped with an error")),
+        None => Ok(()),
+    }
+}
+
 fn force_focus(
     winit_windows: Option<NonSend<WinitWindows>>,
     mut created: MessageReader<WindowCreated>,
@@ -133,18 +215,41 @@ fn _core(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
 
     m.add_class::<core::MazeConfig>()?;
     m.add_class::<agent::AgentConfig>()?;
+    m.add_class::<agent::PolicyErrorMode>()?;
+    m.add_class::<agent::BudgetOverrunMode>()?;
+    m.add_class::<agent::BuiltinPolicy>()?;
     m.add_class::<flag::FlagConfig>()?;
     m.add_class::<flag::CapturePointConfig>()?;
     m.add_class::<camera::CameraConfig>()?;
+    m.add_class::<camera::AgentView>()?;
+    m.add_class::<camera::ViewLayout>()?;
+    m.add_class::<reward::RewardConfig>()?;
+    m.add_class::<recording::RecordingConfig>()?;
+    m.add_class::<recording::RecordingFormat>()?;
+    m.add_class::<recording::GridOverlay>()?;
+    m.add_class::<recording::ImageObservationConfig>()?;
+    m.add_class::<recording::ObservationFrame>()?;
 
     m.add_class::<agent::Action>()?;
     m.add_class::<python::game_state::GameState>()?;
     m.add_class::<python::game_state::AgentState>()?;
     m.add_class::<python::game_state::HitInfo>()?;
     m.add_class::<python::game_state::EntityType>()?;
+    m.add_class::<python::game_state::ActionResult>()?;
+    m.add_class::<python::game_state::ActionStatus>()?;
+    m.add_class::<python::game_state::MapMetadata>()?;
+    m.add_class::<python::game_state::PrivilegedInfo>()?;
+    m.add_class::<python::game_state::FlagInfo>()?;
+    m.add_class::<python::game_state::CapturePointInfo>()?;
+    m.add_class::<flag::FlagStatus>()?;
     m.add_class::<occupancy_grid::OccupancyGrid>()?;
+    m.add_class::<occupancy_grid::OccupancyGridView>()?;
+    m.add_class::<occupancy_grid::CostMapConfig>()?;
     m.add_class::<occupancy_grid::OccupancyCellView>()?;
+    m.add_class::<debug_draw::DebugDraw>()?;
     m.add_class::<python::game_state::SensorConfidence>()?;
+    m.add_class::<python::summary::EvaluationSummary>()?;
+    m.add_class::<python::summary::LatencyStats>()?;
 
     Ok(())
 }
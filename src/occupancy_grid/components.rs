@@ -4,6 +4,7 @@ use bevy::prelude::*;
 use pyo3::prelude::*;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
 
+use crate::occupancy_grid::CostMapConfig;
 use crate::python::game_state::EntityType;
 
 #[derive(Resource, Default, Debug, Clone, Copy)]
@@ -207,18 +208,29 @@ pub struct OccupancyGrid {
     /// Number of cells in the y direction
     #[pyo3(get)]
     pub height: usize,
+
+    /// World (x, y) coordinates of the corner of cell (0, 0). Column indexes grow along +x and
+    /// row indexes along +y, which points down on the screen.
+    #[pyo3(get)]
+    pub origin: (f32, f32),
 }
 
 #[gen_stub_pymethods]
 #[pymethods]
 impl OccupancyGrid {
+    /// Creates an empty grid. Without an `origin`, the grid is centered on the world origin.
     #[new]
-    pub fn new(width: usize, height: usize, cell_size: f32) -> Self {
+    #[pyo3(signature = (width, height, cell_size, origin=None))]
+    pub fn new(width: usize, height: usize, cell_size: f32, origin: Option<(f32, f32)>) -> Self {
         Self {
             grid: vec![OccupancyGridEntry::default(); width * height],
             cell_size,
             width,
             height,
+            origin: origin.unwrap_or((
+                -(width as f32) * cell_size * 0.5,
+                -(height as f32) * cell_size * 0.5,
+            )),
         }
     }
 
@@ -246,6 +258,235 @@ impl OccupancyGrid {
     pub fn shape(&self) -> (usize, usize) {
         (self.width, self.height)
     }
+
+    /// The (x, y) index of the cell containing a world (x, y) position, or None if it is outside
+    /// the grid. The world y coordinate is the z axis of the 3D scene, as in `GameState`.
+    pub fn world_to_cell(&self, position: (f32, f32)) -> Option<(usize, usize)> {
+        let col = ((position.0 - self.origin.0) / self.cell_size).floor();
+        let row = ((position.1 - self.origin.1) / self.cell_size).floor();
+        if col < 0.0 || row < 0.0 || col >= self.width as f32 || row >= self.height as f32 {
+            return None;
+        }
+        Some((col as usize, row as usize))
+    }
+
+    /// The world (x, y) position of the center of a cell.
+    pub fn cell_to_world(&self, cell: (usize, usize)) -> (f32, f32) {
+        (
+            self.origin.0 + (cell.0 as f32 + 0.5) * self.cell_size,
+            self.origin.1 + (cell.1 as f32 + 0.5) * self.cell_size,
+        )
+    }
+
+    /// The cells overlapping the axis-aligned box between the world positions `min` and `max`,
+    /// row by row. Cells the box only touches along an edge are left out.
+    pub fn cells_in_aabb(&self, min: (f32, f32), max: (f32, f32)) -> Vec<(usize, usize)> {
+        let min_col = ((min.0 - self.origin.0) / self.cell_size).floor().max(0.0);
+        let min_row = ((min.1 - self.origin.1) / self.cell_size).floor().max(0.0);
+        let max_col =
+            (((max.0 - self.origin.0) / self.cell_size).ceil() - 1.0).min(self.width as f32 - 1.0);
+        let max_row =
+            (((max.1 - self.origin.1) / self.cell_size).ceil() - 1.0).min(self.height as f32 - 1.0);
+        if max_col < min_col || max_row < min_row {
+            return Vec::new();
+        }
+
+        let (min_col, min_row) = (min_col as usize, min_row as usize);
+        let (max_col, max_row) = (max_col as usize, max_row as usize);
+        (min_row..=max_row)
+            .flat_map(|row| (min_col..=max_col).map(move |col| (col, row)))
+            .collect()
+    }
+
+    /// The cells a straight line from `start` to `end` passes through, in order, including the
+    /// cells containing both ends. The parts of the line outside the grid are skipped.
+    pub fn ray_cells(&self, start: (f32, f32), end: (f32, f32)) -> Vec<(usize, usize)> {
+        // Work in units of cells, relative to the corner of cell (0, 0).
+        let from = Vec2::new(start.0 - self.origin.0, start.1 - self.origin.1) / self.cell_size;
+        let to = Vec2::new(end.0 - self.origin.0, end.1 - self.origin.1) / self.cell_size;
+        let delta = to - from;
+
+        // Clip the line to the grid, so a far-away end does not cost a long walk.
+        let (mut t_min, mut t_max) = (0.0f32, 1.0f32);
+        for (p, d, size) in [
+            (from.x, delta.x, self.width as f32),
+            (from.y, delta.y, self.height as f32),
+        ] {
+            if d == 0.0 {
+                if p < 0.0 || p >= size {
+                    return Vec::new();
+                }
+                continue;
+            }
+            let (t0, t1) = ((0.0 - p) / d, (size - p) / d);
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+        }
+        if t_min > t_max {
+            return Vec::new();
+        }
+
+        let max_cell = IVec2::new(self.width as i32 - 1, self.height as i32 - 1);
+        let clip = |t: f32| {
+            (from + delta * t)
+                .floor()
+                .as_ivec2()
+                .clamp(IVec2::ZERO, max_cell)
+        };
+        let (mut cell, last) = (clip(t_min), clip(t_max));
+
+        // Walk cell by cell, always crossing whichever cell boundary the line reaches first.
+        let step = IVec2::new(delta.x.signum() as i32, delta.y.signum() as i32);
+        let t_delta = Vec2::new(1.0 / delta.x.abs(), 1.0 / delta.y.abs());
+        let boundary = |c: i32, s: i32| (c + (s > 0) as i32) as f32;
+        let mut t_next = Vec2::new(
+            if step.x != 0 {
+                (boundary(cell.x, step.x) - from.x) / delta.x
+            } else {
+                f32::INFINITY
+            },
+            if step.y != 0 {
+                (boundary(cell.y, step.y) - from.y) / delta.y
+            } else {
+                f32::INFINITY
+            },
+        );
+
+        let mut cells = vec![(cell.x as usize, cell.y as usize)];
+        while cell != last {
+            if t_next.x < t_next.y {
+                cell.x += step.x;
+                t_next.x += t_delta.x;
+            } else {
+                cell.y += step.y;
+                t_next.y += t_delta.y;
+            }
+            if cell.x < 0 || cell.y < 0 || cell.x > max_cell.x || cell.y > max_cell.y {
+                break;
+            }
+            cells.push((cell.x as usize, cell.y as usize));
+        }
+        cells
+    }
+
+    /// Known free cells that share an edge with an unassigned cell, as (x, y) indexes.
+    #[pyo3(name = "frontier_cells")]
+    pub fn py_frontier_cells(&self) -> Vec<(usize, usize)> {
+        self.frontier_cells()
+    }
+
+    /// The frontier cells grouped into clusters that touch, including diagonally.
+    #[pyo3(name = "frontier_clusters")]
+    pub fn py_frontier_clusters(&self) -> Vec<Vec<(usize, usize)>> {
+        self.frontier_clusters()
+    }
+
+    /// The extra cost of entering each cell, indexed as `costs[x][y]`. Impassable cells are
+    /// `inf`. Uses the default `CostMapConfig` if none is given.
+    #[pyo3(name = "cost_map", signature = (config=None))]
+    pub fn py_cost_map(&self, py: Python<'_>, config: Option<CostMapConfig>) -> Vec<Vec<f32>> {
+        let grid = self.clone();
+        py.detach(move || grid.cost_map_columns(&config.unwrap_or_default()))
+    }
+
+    /// Finds the cheapest path from `start` to `goal` with A*, as the world positions of the
+    /// centers of the cells along it, from the start cell to the goal cell. Returns None if the
+    /// goal cannot be reached or either end is outside the grid.
+    #[pyo3(name = "find_path", signature = (start, goal, config=None))]
+    pub fn py_find_path(
+        &self,
+        py: Python<'_>,
+        start: (f32, f32),
+        goal: (f32, f32),
+        config: Option<CostMapConfig>,
+    ) -> Option<Vec<(f32, f32)>> {
+        let grid = self.clone();
+        py.detach(move || grid.find_path(start, goal, &config.unwrap_or_default()))
+    }
+
+    /// The cheapest cost of getting from each cell to the closest of `goals`, indexed as
+    /// `distances[x][y]`, with Dijkstra's algorithm. Moving costs the distance travelled plus the
+    /// cost map entry of every cell entered. Unreachable cells are `inf`.
+    #[pyo3(name = "distance_transform", signature = (goals, config=None))]
+    pub fn py_distance_transform(
+        &self,
+        py: Python<'_>,
+        goals: Vec<(f32, f32)>,
+        config: Option<CostMapConfig>,
+    ) -> Vec<Vec<f32>> {
+        let grid = self.clone();
+        py.detach(move || grid.distance_transform(&goals, &config.unwrap_or_default()))
+    }
+}
+
+impl OccupancyGrid {
+    /// Compares this grid against `truth`, returning (wrong, total) over the cells whose true
+    /// assignment is a wall or free space. Flags and capture points move, so they are ignored.
+    pub fn mapping_errors(&self, truth: &OccupancyGrid) -> (usize, usize) {
+        let mut error = 0;
+        let mut total = 0;
+        for (player_entry, true_entry) in self.grid.iter().zip(truth.grid.iter()) {
+            if let Some(true_entity_type) = true_entry.assignment
+                && true_entity_type != EntityType::Flag
+                && true_entity_type != EntityType::CapturePoint
+            {
+                total += 1;
+                if player_entry.assignment != true_entry.assignment {
+                    error += 1;
+                }
+            }
+        }
+        (error, total)
+    }
+
+    /// `cost_map`, indexed as `costs[x][y]`.
+    pub fn cost_map_columns(&self, config: &CostMapConfig) -> Vec<Vec<f32>> {
+        self.columns(&self.cost_map(config))
+    }
+
+    /// `find_path_cells` between world positions, as the centers of the cells along the path.
+    pub fn find_path(
+        &self,
+        start: (f32, f32),
+        goal: (f32, f32),
+        config: &CostMapConfig,
+    ) -> Option<Vec<(f32, f32)>> {
+        let start = self.world_to_cell(start)?;
+        let goal = self.world_to_cell(goal)?;
+        let costs = self.cost_map(config);
+        let path = self.find_path_cells(start, &[goal], &costs)?;
+        Some(
+            path.into_iter()
+                .map(|cell| self.cell_to_world(cell))
+                .collect(),
+        )
+    }
+
+    /// `dijkstra` from world positions, indexed as `distances[x][y]`.
+    pub fn distance_transform(
+        &self,
+        goals: &[(f32, f32)],
+        config: &CostMapConfig,
+    ) -> Vec<Vec<f32>> {
+        let goals = goals
+            .iter()
+            .filter_map(|&goal| self.world_to_cell(goal))
+            .collect::<Vec<_>>();
+        let costs = self.cost_map(config);
+        let distances = self.dijkstra(&goals, &costs, f32::INFINITY);
+        self.columns(&distances)
+    }
+
+    /// Splits a row-major per-cell buffer into columns, so Python can index it as `[x][y]`.
+    fn columns(&self, values: &[f32]) -> Vec<Vec<f32>> {
+        (0..self.width)
+            .map(|x| {
+                (0..self.height)
+                    .map(|y| values[x + y * self.width])
+                    .collect()
+            })
+            .collect()
+    }
 }
 
 #[gen_stub_pyclass]
@@ -300,6 +541,151 @@ impl OccupancyGridView {
             Ok(grid_ref.shape())
         })
     }
+
+    #[getter]
+    pub fn origin(&self) -> PyResult<(f32, f32)> {
+        Python::attach(|py| {
+            let grid = self.inner.read().unwrap();
+            let grid_ref = grid.borrow(py);
+            Ok(grid_ref.origin)
+        })
+    }
+
+    /// See `OccupancyGrid.world_to_cell`.
+    pub fn world_to_cell(&self, position: (f32, f32)) -> PyResult<Option<(usize, usize)>> {
+        Python::attach(|py| {
+            let grid = self.inner.read().unwrap();
+            let grid_ref = grid.borrow(py);
+            Ok(grid_ref.world_to_cell(position))
+        })
+    }
+
+    /// See `OccupancyGrid.cell_to_world`.
+    pub fn cell_to_world(&self, cell: (usize, usize)) -> PyResult<(f32, f32)> {
+        Python::attach(|py| {
+            let grid = self.inner.read().unwrap();
+            let grid_ref = grid.borrow(py);
+            Ok(grid_ref.cell_to_world(cell))
+        })
+    }
+
+    /// See `OccupancyGrid.cells_in_aabb`.
+    pub fn cells_in_aabb(&self, min: (f32, f32), max: (f32, f32)) -> PyResult<Vec<(usize, usize)>> {
+        Python::attach(|py| {
+            let grid = self.inner.read().unwrap();
+            let grid_ref = grid.borrow(py);
+            Ok(grid_ref.cells_in_aabb(min, max))
+        })
+    }
+
+    /// See `OccupancyGrid.ray_cells`.
+    pub fn ray_cells(&self, start: (f32, f32), end: (f32, f32)) -> PyResult<Vec<(usize, usize)>> {
+        Python::attach(|py| {
+            let grid = self.inner.read().unwrap();
+            let grid_ref = grid.borrow(py);
+            Ok(grid_ref.ray_cells(start, end))
+        })
+    }
+
+    /// See `OccupancyGrid.frontier_cells`.
+    pub fn frontier_cells(&self) -> PyResult<Vec<(usize, usize)>> {
+        Python::attach(|py| {
+            let grid = self.inner.read().unwrap();
+            let grid_ref = grid.borrow(py);
+            Ok(grid_ref.frontier_cells())
+        })
+    }
+
+    /// See `OccupancyGrid.frontier_clusters`.
+    pub fn frontier_clusters(&self) -> PyResult<Vec<Vec<(usize, usize)>>> {
+        Python::attach(|py| {
+            let grid = self.inner.read().unwrap();
+            let grid_ref = grid.borrow(py);
+            Ok(grid_ref.frontier_clusters())
+        })
+    }
+
+    /// See `OccupancyGrid.cost_map`.
+    #[pyo3(signature = (config=None))]
+    pub fn cost_map(
+        &self,
+        py: Python<'_>,
+        config: Option<CostMapConfig>,
+    ) -> PyResult<Vec<Vec<f32>>> {
+        let grid = self.snapshot(py);
+        Ok(py.detach(move || grid.cost_map_columns(&config.unwrap_or_default())))
+    }
+
+    /// See `OccupancyGrid.find_path`.
+    #[pyo3(signature = (start, goal, config=None))]
+    pub fn find_path(
+        &self,
+        py: Python<'_>,
+        start: (f32, f32),
+        goal: (f32, f32),
+        config: Option<CostMapConfig>,
+    ) -> PyResult<Option<Vec<(f32, f32)>>> {
+        let grid = self.snapshot(py);
+        Ok(py.detach(move || grid.find_path(start, goal, &config.unwrap_or_default())))
+    }
+
+    /// See `OccupancyGrid.distance_transform`.
+    #[pyo3(signature = (goals, config=None))]
+    pub fn distance_transform(
+        &self,
+        py: Python<'_>,
+        goals: Vec<(f32, f32)>,
+        config: Option<CostMapConfig>,
+    ) -> PyResult<Vec<Vec<f32>>> {
+        let grid = self.snapshot(py);
+        Ok(py.detach(move || grid.distance_transform(&goals, &config.unwrap_or_default())))
+    }
+}
+
+impl OccupancyGridView {
+    /// A copy of the grid, for searches that run without holding the GIL or the grid, which
+    /// the simulation keeps updating meanwhile.
+    fn snapshot(&self, py: Python<'_>) -> OccupancyGrid {
+        self.inner.read().unwrap().borrow(py).clone()
+    }
+}
+
+/// Seconds of simulated time between samples of the coverage curve.
+pub const COVERAGE_SAMPLE_INTERVAL: f32 = 1.0;
+
+/// How much of the maze the player grid has mapped, over the run.
+#[derive(Resource, Debug, Default)]
+pub struct ExplorationTracker {
+    /// The true grid cells reachable from where the agent spawned, indexed like `grid`.
+    pub reachable: Option<Vec<bool>>,
+    /// The explored fraction of the reachable cells, as (elapsed seconds, fraction) samples.
+    pub coverage: Vec<(f32, f32)>,
+}
+
+impl ExplorationTracker {
+    /// The fraction of the reachable cells that `player_grid` has assigned, or 0 before the
+    /// reachable cells are known.
+    pub fn explored_fraction(&self, player_grid: &OccupancyGrid) -> f32 {
+        let Some(reachable) = &self.reachable else {
+            return 0.0;
+        };
+        let (explored, total) = player_grid.explored_cells(reachable);
+        explored as f32 / total.max(1) as f32
+    }
+}
+
+/// How many player grid cells disagree with the true grid, as `update_mapping_error` last counted.
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct MappingError {
+    pub errors: usize,
+    pub total: usize,
+}
+
+impl MappingError {
+    /// The fraction of the compared cells that disagree.
+    pub fn fraction(&self) -> f32 {
+        self.errors as f32 / self.total.max(1) as f32
+    }
 }
 
 #[derive(Resource, Clone)]
@@ -338,3 +724,126 @@ impl PyGridProvider for TrueGrid {
         "TruthGrid"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 4x3 grid of 2-unit cells whose corner is away from the world origin.
+    fn offset_grid() -> OccupancyGrid {
+        OccupancyGrid::new(4, 3, 2.0, Some((10.0, -5.0)))
+    }
+
+    #[test]
+    fn grid_is_centered_without_origin() {
+        let grid = OccupancyGrid::new(4, 2, 1.0, None);
+        assert_eq!(grid.origin, (-2.0, -1.0));
+        assert_eq!(grid.world_to_cell((0.0, 0.0)), Some((2, 1)));
+    }
+
+    #[test]
+    fn world_to_cell_with_offset_origin() {
+        let grid = offset_grid();
+        assert_eq!(grid.world_to_cell((10.0, -5.0)), Some((0, 0)));
+        assert_eq!(grid.world_to_cell((17.9, 0.9)), Some((3, 2)));
+        // The far edges belong to no cell, like everything before the origin.
+        assert_eq!(grid.world_to_cell((18.0, 0.0)), None);
+        assert_eq!(grid.world_to_cell((9.9, -5.0)), None);
+        assert_eq!(grid.world_to_cell((10.0, 1.0)), None);
+
+        assert_eq!(grid.cell_to_world((3, 2)), (17.0, 0.0));
+        for row in 0..grid.height {
+            for col in 0..grid.width {
+                let center = grid.cell_to_world((col, row));
+                assert_eq!(grid.world_to_cell(center), Some((col, row)));
+            }
+        }
+    }
+
+    #[test]
+    fn cells_in_aabb_leaves_out_touching_cells() {
+        let grid = OccupancyGrid::new(4, 4, 1.0, Some((0.0, 0.0)));
+        assert_eq!(grid.cells_in_aabb((1.0, 1.0), (2.0, 2.0)), vec![(1, 1)]);
+        assert_eq!(
+            grid.cells_in_aabb((0.5, 0.5), (1.5, 1.5)),
+            vec![(0, 0), (1, 0), (0, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn cells_in_aabb_clips_to_the_grid() {
+        let grid = offset_grid();
+        assert_eq!(
+            grid.cells_in_aabb((0.0, -100.0), (11.0, 100.0)),
+            vec![(0, 0), (0, 1), (0, 2)]
+        );
+        assert!(grid.cells_in_aabb((18.0, -5.0), (20.0, 1.0)).is_empty());
+    }
+
+    #[test]
+    fn ray_cells_along_axes() {
+        let grid = OccupancyGrid::new(4, 4, 1.0, Some((0.0, 0.0)));
+        assert_eq!(
+            grid.ray_cells((0.5, 1.5), (3.5, 1.5)),
+            vec![(0, 1), (1, 1), (2, 1), (3, 1)]
+        );
+        assert_eq!(
+            grid.ray_cells((2.5, 3.5), (2.5, 0.5)),
+            vec![(2, 3), (2, 2), (2, 1), (2, 0)]
+        );
+        assert_eq!(grid.ray_cells((1.5, 1.5), (1.5, 1.5)), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn ray_cells_ending_on_cell_boundaries() {
+        let grid = OccupancyGrid::new(4, 4, 1.0, Some((0.0, 0.0)));
+        // An end on a boundary is in the cell after it, as with `world_to_cell`.
+        assert_eq!(
+            grid.ray_cells((1.0, 0.5), (3.0, 0.5)),
+            vec![(1, 0), (2, 0), (3, 0)]
+        );
+        // Except on the far edge of the grid, which is outside it.
+        assert_eq!(grid.ray_cells((2.5, 0.5), (4.0, 0.5)), vec![(2, 0), (3, 0)]);
+        // Through cell corners, the line moves through one of the two cells beside each corner.
+        let diagonal = grid.ray_cells((0.5, 0.5), (2.5, 2.5));
+        assert_eq!(diagonal.first(), Some(&(0, 0)));
+        assert_eq!(diagonal.last(), Some(&(2, 2)));
+        assert_eq!(diagonal.len(), 5);
+        for pair in diagonal.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert_eq!(a.0.abs_diff(b.0) + a.1.abs_diff(b.1), 1);
+        }
+    }
+
+    #[test]
+    fn ray_cells_off_the_grid() {
+        let grid = OccupancyGrid::new(4, 4, 1.0, Some((0.0, 0.0)));
+        assert_eq!(
+            grid.ray_cells((-10.0, 0.5), (10.0, 0.5)),
+            vec![(0, 0), (1, 0), (2, 0), (3, 0)]
+        );
+        assert_eq!(
+            grid.ray_cells((-3.0, 2.5), (1.5, 2.5)),
+            vec![(0, 2), (1, 2)]
+        );
+        assert_eq!(
+            grid.ray_cells((2.5, 1.5), (2.5, 9.0)),
+            vec![(2, 1), (2, 2), (2, 3)]
+        );
+        assert!(grid.ray_cells((-1.0, -1.0), (-1.0, 5.0)).is_empty());
+        assert!(grid.ray_cells((5.0, 0.0), (8.0, 3.0)).is_empty());
+    }
+
+    #[test]
+    fn ray_cells_with_offset_origin() {
+        let grid = offset_grid();
+        assert_eq!(
+            grid.ray_cells((11.0, -4.0), (17.0, -4.0)),
+            vec![(0, 0), (1, 0), (2, 0), (3, 0)]
+        );
+        assert_eq!(
+            grid.ray_cells((13.0, -10.0), (13.0, 10.0)),
+            vec![(1, 0), (1, 1), (1, 2)]
+        );
+    }
+}
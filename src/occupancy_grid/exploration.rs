@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+
+use crate::occupancy_grid::OccupancyGrid;
+use crate::python::game_state::EntityType;
+
+const SIDES: [(i64, i64); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+impl OccupancyGrid {
+    /// The cells in bounds next to (x, y), across an edge.
+    fn sides(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        SIDES.into_iter().filter_map(move |(dx, dy)| {
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            (nx >= 0 && ny >= 0 && (nx as usize) < self.width && (ny as usize) < self.height)
+                .then_some((nx as usize, ny as usize))
+        })
+    }
+
+    /// Known free cells that share an edge with an unassigned cell, row by row.
+    pub fn frontier_cells(&self) -> Vec<(usize, usize)> {
+        let mut frontiers = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.grid[x + y * self.width].assignment != Some(EntityType::Empty) {
+                    continue;
+                }
+                if self
+                    .sides(x, y)
+                    .any(|(nx, ny)| self.grid[nx + ny * self.width].assignment.is_none())
+                {
+                    frontiers.push((x, y));
+                }
+            }
+        }
+        frontiers
+    }
+
+    /// The frontier cells grouped into clusters that touch, including diagonally. Clusters are
+    /// ordered by their first cell, row by row.
+    pub fn frontier_clusters(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut is_frontier = vec![false; self.grid.len()];
+        for (x, y) in self.frontier_cells() {
+            is_frontier[x + y * self.width] = true;
+        }
+
+        let mut clusters = Vec::new();
+        for start in 0..self.grid.len() {
+            if !is_frontier[start] {
+                continue;
+            }
+            is_frontier[start] = false;
+
+            let mut cluster = Vec::new();
+            let mut queue = VecDeque::from([start]);
+            while let Some(index) = queue.pop_front() {
+                let (x, y) = ((index % self.width) as i64, (index / self.width) as i64);
+                cluster.push((x as usize, y as usize));
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        let (nx, ny) = (x + dx, y + dy);
+                        if nx < 0
+                            || ny < 0
+                            || nx as usize >= self.width
+                            || ny as usize >= self.height
+                        {
+                            continue;
+                        }
+                        let neighbor = nx as usize + ny as usize * self.width;
+                        if is_frontier[neighbor] {
+                            is_frontier[neighbor] = false;
+                            queue.push_back(neighbor);
+                        }
+                    }
+                }
+            }
+            clusters.push(cluster);
+        }
+        clusters
+    }
+
+    /// Which cells can be walked to from `start` without crossing a wall, indexed like `grid`.
+    pub fn reachable_cells(&self, start: (usize, usize)) -> Vec<bool> {
+        let mut reachable = vec![false; self.grid.len()];
+        let passable =
+            |x: usize, y: usize| self.grid[x + y * self.width].assignment != Some(EntityType::Wall);
+        if start.0 >= self.width || start.1 >= self.height || !passable(start.0, start.1) {
+            return reachable;
+        }
+
+        reachable[start.0 + start.1 * self.width] = true;
+        let mut queue = VecDeque::from([start]);
+        while let Some((x, y)) = queue.pop_front() {
+            for (nx, ny) in self.sides(x, y) {
+                let index = nx + ny * self.width;
+                if !reachable[index] && passable(nx, ny) {
+                    reachable[index] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Counts the cells marked in `reachable` that this grid has assigned, returning
+    /// (explored, total).
+    pub fn explored_cells(&self, reachable: &[bool]) -> (usize, usize) {
+        let mut explored = 0;
+        let mut total = 0;
+        for (entry, &reachable) in self.grid.iter().zip(reachable) {
+            if reachable {
+                total += 1;
+                if entry.assignment.is_some() {
+                    explored += 1;
+                }
+            }
+        }
+        (explored, total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::occupancy_grid::ExplorationTracker;
+
+    /// A grid drawn row by row from y = 0: `.` is free, `#` a wall and `?` unassigned.
+    fn grid_from_rows(rows: &[&str]) -> OccupancyGrid {
+        let mut grid = OccupancyGrid::new(rows[0].len(), rows.len(), 1.0, None);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                grid.grid[x + y * grid.width].assignment = match c {
+                    '.' => Some(EntityType::Empty),
+                    '#' => Some(EntityType::Wall),
+                    _ => None,
+                };
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn diagonal_frontier_cells_form_one_cluster() {
+        let grid = grid_from_rows(&["?.#.?", ".####"]);
+        assert_eq!(grid.frontier_cells(), vec![(1, 0), (3, 0), (0, 1)]);
+        assert_eq!(
+            grid.frontier_clusters(),
+            vec![vec![(1, 0), (0, 1)], vec![(3, 0)]]
+        );
+    }
+
+    #[test]
+    fn no_frontiers_on_a_mapped_grid() {
+        let grid = grid_from_rows(&["..#", "#.."]);
+        assert!(grid.frontier_cells().is_empty());
+        assert!(grid.frontier_clusters().is_empty());
+    }
+
+    #[test]
+    fn walls_meeting_at_a_corner_cut_off_reachability() {
+        let grid = grid_from_rows(&[".#.", "#.?"]);
+        assert_eq!(
+            grid.reachable_cells((0, 0)),
+            vec![true, false, false, false, false, false]
+        );
+        // Unassigned cells are passable.
+        assert_eq!(
+            grid.reachable_cells((2, 0)),
+            vec![false, false, true, false, true, true]
+        );
+        assert!(!grid.reachable_cells((1, 0)).contains(&true));
+        assert!(!grid.reachable_cells((3, 0)).contains(&true));
+    }
+
+    #[test]
+    fn explored_fraction_of_a_partly_mapped_grid() {
+        let truth = grid_from_rows(&["..#", "..#"]);
+        let player = grid_from_rows(&["..?", "??#"]);
+        let reachable = truth.reachable_cells((0, 0));
+        assert_eq!(player.explored_cells(&reachable), (2, 4));
+
+        let mut tracker = ExplorationTracker::default();
+        assert_eq!(tracker.explored_fraction(&player), 0.0);
+        tracker.reachable = Some(reachable);
+        assert_eq!(tracker.explored_fraction(&player), 0.5);
+    }
+}
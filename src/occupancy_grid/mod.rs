@@ -1,32 +1,102 @@
 mod components;
+mod exploration;
+mod planning;
 mod systems;
 
 use std::sync::{Arc, RwLock};
 
 use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use derivative::Derivative;
 use pyo3::prelude::*;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use serde::{Deserialize, Serialize};
 
 pub use components::*;
+pub use systems::encode_grid_to_rgba;
 
 use crate::core::MazeConfig;
 
+/// How the planners on `OccupancyGrid` weigh cells. Distances are in world units.
+#[gen_stub_pyclass]
+#[pyclass(name = "CostMapConfig")]
+#[derive(Debug, Clone, Reflect, Derivative, Serialize, Deserialize)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct CostMapConfig {
+    /// Cells whose wall probability is at least this are impassable, as are cells assigned to
+    /// walls.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "0.5"))]
+    pub wall_threshold: f32,
+
+    /// Cells whose center is closer than this to an impassable cell cost extra to enter.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "1.0"))]
+    pub inflation_radius: f32,
+
+    /// The extra cost of a cell right next to an impassable cell, falling off linearly to zero
+    /// at `inflation_radius`.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "5.0"))]
+    pub inflation_cost: f32,
+
+    /// The extra cost of entering a cell with no assignment.
+    #[pyo3(get, set)]
+    pub unknown_cost: f32,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl CostMapConfig {
+    #[new]
+    #[pyo3(signature = (wall_threshold=0.5, inflation_radius=1.0, inflation_cost=5.0, unknown_cost=0.0))]
+    pub fn new(
+        wall_threshold: f32,
+        inflation_radius: f32,
+        inflation_cost: f32,
+        unknown_cost: f32,
+    ) -> Self {
+        Self {
+            wall_threshold,
+            inflation_radius,
+            inflation_cost,
+            unknown_cost,
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("CostMapConfig({})", self.__str__()?))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to serialize CostMapConfig: {}",
+                e
+            ))
+        })
+    }
+}
+
 pub struct OccupancyGridPlugin {
     pub config: MazeConfig,
 }
 
 impl Plugin for OccupancyGridPlugin {
     fn build(&self, app: &mut App) {
-        let width = (self.config.maze_generation.width / self.config.agent.occupancy_grid_cell_size)
-            .round() as usize;
-        let height = (self.config.maze_generation.height
-            / self.config.agent.occupancy_grid_cell_size)
-            .round() as usize;
+        let (width, height) = self.config.occupancy_grid_size();
+        let origin = Some(self.config.maze_generation.origin().into());
 
         let true_arc = {
             let true_grid = Python::attach(|py| {
                 Py::new(
                     py,
-                    OccupancyGrid::new(width, height, self.config.agent.occupancy_grid_cell_size),
+                    OccupancyGrid::new(
+                        width,
+                        height,
+                        self.config.agent.occupancy_grid_cell_size,
+                        origin,
+                    ),
                 )
             })
             .expect("Failed to create OccupancyGrid");
@@ -38,7 +108,12 @@ impl Plugin for OccupancyGridPlugin {
             let player_grid = Python::attach(|py| {
                 Py::new(
                     py,
-                    OccupancyGrid::new(width, height, self.config.agent.occupancy_grid_cell_size),
+                    OccupancyGrid::new(
+                        width,
+                        height,
+                        self.config.agent.occupancy_grid_cell_size,
+                        origin,
+                    ),
                 )
             })
             .expect("Failed to create OccupancyGrid");
@@ -52,6 +127,9 @@ impl Plugin for OccupancyGridPlugin {
             cell: None,
             world_hit: None,
         });
+        app.init_resource::<ExplorationTracker>();
+        app.init_resource::<MappingError>();
+        app.add_systems(Update, systems::track_coverage);
 
         app.add_systems(
             Startup,
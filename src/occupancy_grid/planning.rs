@@ -0,0 +1,316 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::occupancy_grid::{CostMapConfig, OccupancyGrid};
+use crate::python::game_state::EntityType;
+
+const NEIGHBORS: [(i64, i64); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// Above this many goals, the search runs without a heuristic rather than measuring the distance
+/// to every goal at every step.
+const MAX_HEURISTIC_GOALS: usize = 16;
+
+/// An entry in the search's open set, ordered so that `BinaryHeap` pops the cheapest first.
+#[derive(PartialEq)]
+struct Open {
+    priority: f32,
+    cost: f32,
+    index: usize,
+}
+
+impl Eq for Open {}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.total_cmp(&self.priority)
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn octile_distance(dx: f32, dy: f32) -> f32 {
+    let (dx, dy) = (dx.abs(), dy.abs());
+    dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)
+}
+
+impl OccupancyGrid {
+    /// The extra cost of entering each cell, indexed like `grid`. Walls, and cells whose wall
+    /// probability reaches `wall_threshold`, cost infinity. Cells whose center is within
+    /// `inflation_radius` of the edge of one cost up to `inflation_cost`, falling off linearly
+    /// with distance, and unassigned cells cost `unknown_cost` on top.
+    pub fn cost_map(&self, config: &CostMapConfig) -> Vec<f32> {
+        let lethal = self
+            .grid
+            .iter()
+            .map(|entry| {
+                entry.assignment == Some(EntityType::Wall)
+                    || entry.probabilities().1 >= config.wall_threshold
+            })
+            .collect::<Vec<_>>();
+
+        let lethal_cells = (0..self.grid.len())
+            .filter(|&index| lethal[index])
+            .map(|index| (index % self.width, index / self.width))
+            .collect::<Vec<_>>();
+        let no_costs = vec![0.0; self.grid.len()];
+        let half_cell = self.cell_size * 0.5;
+        let wall_distance = self
+            .dijkstra(
+                &lethal_cells,
+                &no_costs,
+                config.inflation_radius + half_cell,
+            )
+            .into_iter()
+            .map(|distance| distance - half_cell)
+            .collect::<Vec<_>>();
+
+        (0..self.grid.len())
+            .map(|index| {
+                if lethal[index] {
+                    return f32::INFINITY;
+                }
+                let mut cost = 0.0;
+                if config.inflation_radius > 0.0 && wall_distance[index] < config.inflation_radius {
+                    cost += config.inflation_cost
+                        * (1.0 - wall_distance[index] / config.inflation_radius);
+                }
+                if self.grid[index].assignment.is_none() {
+                    cost += config.unknown_cost;
+                }
+                cost
+            })
+            .collect()
+    }
+
+    /// Finds the cheapest 8-connected path of cells from `start` to the closest of `goals`,
+    /// inclusive of both ends. Entering a cell costs the distance moved, in world units, plus its
+    /// entry in `costs` (see `cost_map`). Cells with infinite cost are never entered and their
+    /// corners never cut.
+    pub fn find_path_cells(
+        &self,
+        start: (usize, usize),
+        goals: &[(usize, usize)],
+        costs: &[f32],
+    ) -> Option<Vec<(usize, usize)>> {
+        if start.0 >= self.width || start.1 >= self.height {
+            return None;
+        }
+
+        let mut is_goal = vec![false; self.grid.len()];
+        for &(x, y) in goals {
+            if x < self.width && y < self.height {
+                is_goal[x + y * self.width] = true;
+            }
+        }
+
+        let heuristic = |x: usize, y: usize| {
+            if goals.len() > MAX_HEURISTIC_GOALS {
+                return 0.0;
+            }
+            goals
+                .iter()
+                .map(|&(gx, gy)| octile_distance(gx as f32 - x as f32, gy as f32 - y as f32))
+                .fold(f32::INFINITY, f32::min)
+                * self.cell_size
+        };
+
+        let start_index = start.0 + start.1 * self.width;
+        let mut came_from = vec![usize::MAX; self.grid.len()];
+        let mut goal = None;
+        self.search(
+            &[start_index],
+            costs,
+            f32::INFINITY,
+            heuristic,
+            |index, from| {
+                came_from[index] = from;
+                if is_goal[index] {
+                    goal = Some(index);
+                    return true;
+                }
+                false
+            },
+        );
+
+        let mut current = goal?;
+        let mut path = vec![(current % self.width, current / self.width)];
+        while came_from[current] != usize::MAX {
+            current = came_from[current];
+            path.push((current % self.width, current / self.width));
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// The cheapest cost, in world units, from every cell to the closest of `sources`, indexed
+    /// like `grid`. Cells that cannot be reached, or only at more than `max_cost`, are infinite.
+    pub fn dijkstra(&self, sources: &[(usize, usize)], costs: &[f32], max_cost: f32) -> Vec<f32> {
+        let sources = sources
+            .iter()
+            .filter(|&&(x, y)| x < self.width && y < self.height)
+            .map(|&(x, y)| x + y * self.width)
+            .collect::<Vec<_>>();
+        self.search(&sources, costs, max_cost, |_, _| 0.0, |_, _| false)
+    }
+
+    /// Best-first search from `sources`. `visit(index, came_from)` is called once per settled
+    /// cell, and stops the search by returning true. Returns the cost of every cell reached.
+    fn search(
+        &self,
+        sources: &[usize],
+        costs: &[f32],
+        max_cost: f32,
+        heuristic: impl Fn(usize, usize) -> f32,
+        mut visit: impl FnMut(usize, usize) -> bool,
+    ) -> Vec<f32> {
+        let mut cost = vec![f32::INFINITY; self.grid.len()];
+        let mut came_from = vec![usize::MAX; self.grid.len()];
+        let mut settled = vec![false; self.grid.len()];
+        let mut open = BinaryHeap::new();
+
+        for &source in sources {
+            cost[source] = 0.0;
+            open.push(Open {
+                priority: heuristic(source % self.width, source / self.width),
+                cost: 0.0,
+                index: source,
+            });
+        }
+
+        while let Some(Open {
+            cost: entry_cost,
+            index,
+            ..
+        }) = open.pop()
+        {
+            if entry_cost > cost[index] || settled[index] {
+                continue; // stale entry
+            }
+            settled[index] = true;
+            if visit(index, came_from[index]) {
+                break;
+            }
+
+            for (neighbor, step) in self.neighbors(index, costs) {
+                let next_cost = cost[index] + step;
+                if next_cost < cost[neighbor] && next_cost <= max_cost {
+                    cost[neighbor] = next_cost;
+                    came_from[neighbor] = index;
+                    open.push(Open {
+                        priority: next_cost
+                            + heuristic(neighbor % self.width, neighbor / self.width),
+                        cost: next_cost,
+                        index: neighbor,
+                    });
+                }
+            }
+        }
+
+        cost
+    }
+
+    /// The cells reachable in one step from `index`, with the cost of entering each.
+    fn neighbors<'a>(
+        &'a self,
+        index: usize,
+        costs: &'a [f32],
+    ) -> impl Iterator<Item = (usize, f32)> + 'a {
+        let (x, y) = ((index % self.width) as i64, (index / self.width) as i64);
+        let blocked = move |x: i64, y: i64| {
+            x < 0
+                || y < 0
+                || x as usize >= self.width
+                || y as usize >= self.height
+                || costs[x as usize + y as usize * self.width].is_infinite()
+        };
+
+        NEIGHBORS.into_iter().filter_map(move |(dx, dy)| {
+            let (nx, ny) = (x + dx, y + dy);
+            if blocked(nx, ny) {
+                return None;
+            }
+            let diagonal = dx != 0 && dy != 0;
+            if diagonal && (blocked(x + dx, y) || blocked(x, y + dy)) {
+                return None;
+            }
+
+            let neighbor = nx as usize + ny as usize * self.width;
+            let step = if diagonal {
+                std::f32::consts::SQRT_2
+            } else {
+                1.0
+            };
+            Some((neighbor, step * self.cell_size + costs[neighbor]))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A grid of known free cells, with walls at `walls`.
+    fn grid_with_walls(width: usize, height: usize, walls: &[(usize, usize)]) -> OccupancyGrid {
+        let mut grid = OccupancyGrid::new(width, height, 1.0, None);
+        for entry in &mut grid.grid {
+            entry.assignment = Some(EntityType::Empty);
+        }
+        for &(x, y) in walls {
+            grid.grid[x + y * width].assignment = Some(EntityType::Wall);
+        }
+        grid
+    }
+
+    /// The cost map without inflation, so that only walls change the path.
+    fn costs(grid: &OccupancyGrid) -> Vec<f32> {
+        grid.cost_map(&CostMapConfig {
+            inflation_radius: 0.0,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn find_path_does_not_cut_corners() {
+        let grid = grid_with_walls(2, 2, &[(1, 0)]);
+        let path = grid.find_path_cells((0, 0), &[(1, 1)], &costs(&grid));
+        assert_eq!(path, Some(vec![(0, 0), (0, 1), (1, 1)]));
+    }
+
+    #[test]
+    fn find_path_to_unreachable_goal() {
+        let grid = grid_with_walls(3, 3, &[(1, 0), (1, 1), (1, 2)]);
+        let costs = costs(&grid);
+        assert_eq!(grid.find_path_cells((0, 0), &[(2, 2)], &costs), None);
+        assert_eq!(grid.find_path_cells((0, 0), &[(1, 1)], &costs), None);
+
+        let distances = grid.dijkstra(&[(0, 0)], &costs, f32::INFINITY);
+        assert!(distances[2 + 2 * grid.width].is_infinite());
+    }
+
+    #[test]
+    fn find_path_from_goal() {
+        let grid = grid_with_walls(3, 3, &[]);
+        let costs = costs(&grid);
+        assert_eq!(
+            grid.find_path_cells((1, 1), &[(1, 1)], &costs),
+            Some(vec![(1, 1)])
+        );
+        assert_eq!(
+            grid.dijkstra(&[(1, 1)], &costs, f32::INFINITY)[1 + grid.width],
+            0.0
+        );
+    }
+}
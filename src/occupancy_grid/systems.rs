@@ -8,16 +8,18 @@ use bevy::{
 use pyo3::prelude::*;
 
 use crate::{
+    agent::{Agent, BuiltinPolicy},
+    camera::MainCamera,
     core::MazeConfig,
     occupancy_grid::{
-        GridPlane, GridVisualization, HoverBox, HoverBoxText, HoverCell, OccupancyGrid,
-        PyGridProvider,
+        COVERAGE_SAMPLE_INTERVAL, ExplorationTracker, GridPlane, GridVisualization, HoverBox,
+        HoverBoxText, HoverCell, OccupancyGrid, PlayerGrid, PyGridProvider, TrueGrid,
     },
     python::game_state::EntityType,
-    scene::WALL_HEIGHT,
+    scene::{WALL_HEIGHT, WallSegments},
 };
 
-pub fn setup_key_instructions(mut commands: Commands) {
+pub fn setup_key_instructions(mut commands: Commands, config: Res<MazeConfig>) {
     commands
         .spawn((
             Node {
@@ -42,16 +44,94 @@ pub fn setup_key_instructions(mut commands: Commands) {
                 TextLayout::new_with_justify(Justify::Right),
             ));
             parent.spawn((
-                Text::new("+/-: Zoom In/Out | Arrow Keys: Pan Camera"),
+                Text::new(
+                    "R: Toggle Rays | L: Toggle True/Estimated Trails | C: Toggle Estimate Uncertainty",
+                ),
                 TextFont {
                     font_size: 14.0,
                     ..default()
                 },
                 TextLayout::new_with_justify(Justify::Right),
             ));
+            parent.spawn((
+                Text::new(
+                    "+/-/Scroll: Zoom | Arrow Keys/Drag: Pan | F: Follow Agent | H: Fit Maze",
+                ),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(Justify::Right),
+            ));
+            parent.spawn((
+                Text::new("V: Cycle Agent View (Off/Scene/Belief) | G: Metrics"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(Justify::Right),
+            ));
+            parent.spawn((
+                Text::new("M: Edit Maze"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(Justify::Right),
+            ));
+            if config.agent.builtin_policy == BuiltinPolicy::Teleop {
+                parent.spawn((
+                    Text::new("WASD: Drive | Space: Pick Up Flag | E: Drop Flag"),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextLayout::new_with_justify(Justify::Right),
+                ));
+            }
         });
 }
 
+/// Samples how much of the reachable maze the player grid has mapped, once per
+/// `COVERAGE_SAMPLE_INTERVAL`. The reachable cells are found again after the walls change.
+pub fn track_coverage(
+    time: Res<Time>,
+    player_grid: Res<PlayerGrid>,
+    true_grid: Res<TrueGrid>,
+    segments: Option<Res<WallSegments>>,
+    agent: Query<&Transform, With<Agent>>,
+    mut tracker: ResMut<ExplorationTracker>,
+) {
+    // The true grid may not show the new walls until `update_true_grid` has run, so wait a frame.
+    if segments.is_some_and(|segments| segments.is_changed()) {
+        tracker.reachable = None;
+        return;
+    }
+
+    let elapsed = time.elapsed_secs();
+    if let Some(&(last, _)) = tracker.coverage.last()
+        && elapsed < last + COVERAGE_SAMPLE_INTERVAL
+    {
+        return;
+    }
+
+    Python::attach(|py| {
+        if tracker.reachable.is_none() {
+            let Ok(transform) = agent.single() else {
+                return;
+            };
+            let true_grid = true_grid.0.read().unwrap();
+            let true_grid = true_grid.borrow(py);
+            let start = true_grid.world_to_cell((transform.translation.x, transform.translation.z));
+            tracker.reachable = start.map(|start| true_grid.reachable_cells(start));
+        }
+
+        let player_grid = player_grid.0.read().unwrap();
+        let fraction = tracker.explored_fraction(&player_grid.borrow(py));
+        tracker.coverage.push((elapsed, fraction));
+    });
+}
+
 #[allow(clippy::type_complexity)]
 pub fn toggle_grid<TOn: PyGridProvider, TOff: PyGridProvider>(
     mut vis_on: Query<&mut Visibility, (With<GridPlane<TOn>>, Without<GridPlane<TOff>>)>,
@@ -181,7 +261,8 @@ pub fn update_grid_texture<T: PyGridProvider>(
     }
 }
 
-fn encode_grid_to_rgba(grid: &OccupancyGrid) -> Vec<u8> {
+/// One RGBA pixel per cell, row by row, colored by the cell's assignment.
+pub fn encode_grid_to_rgba(grid: &OccupancyGrid) -> Vec<u8> {
     let width = grid.width;
     let height = grid.height;
     let mut buffer = vec![0u8; width * height * 4];
@@ -214,11 +295,10 @@ pub fn cursor_to_grid_cell<T: PyGridProvider>(
     // cursor
     windows: Query<&Window, With<PrimaryWindow>>,
     // camera doing the looking
-    cams: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    cams: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     // your grid plane transform
     plane_q: Query<&GlobalTransform, With<GridPlane<T>>>,
-    // your sizes
-    config: Res<MazeConfig>,
+    grid: Res<T>,
     mut hover: ResMut<HoverCell>,
 ) {
     // let window = if let Ok(w) = windows.single() {
@@ -268,27 +348,16 @@ pub fn cursor_to_grid_cell<T: PyGridProvider>(
     let inv = plane_gt.to_matrix().inverse();
     let local = inv.transform_point3(hit); // local.y should be ~0
 
-    // Map local.x/local.z to [0, width)×[0, height)
-    let world_w = config.maze_generation.width;
-    let world_h = config.maze_generation.height;
-    let cell = config.agent.occupancy_grid_cell_size;
-    let grid_w = (world_w / cell).round() as u32;
-    let grid_h = (world_h / cell).round() as u32;
-
-    // Plane is centered at (0, WALL_HEIGHT, 0) with extents ±world_w/2, ±world_h/2
-    let u = (local.x + world_w * 0.5) / cell; // column (x)
-    let v = (local.z + world_h * 0.5) / cell; // row    (z)
-
-    let col = u.floor() as i32;
-    let row = v.floor() as i32;
-
-    // Inside?
-    if col < 0 || row < 0 || col as u32 >= grid_w || row as u32 >= grid_h {
+    // Plane is centered at (0, WALL_HEIGHT, 0), so local.x/local.z are world x/z
+    let cell = Python::attach(|py| {
+        let grid = grid.arc().read().unwrap();
+        grid.borrow(py).world_to_cell((local.x, local.z))
+    });
+    let Some((col, row)) = cell else {
         *hover = HoverCell::default();
         return;
-    }
+    };
 
-    // If your image ends up vertically flipped, swap to: let row = (grid_h as i32 - 1) - row;
     hover.cell = Some(UVec2::new(col as u32, row as u32));
     hover.world_hit = Some(hit);
 }
@@ -0,0 +1,202 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use pyo3::prelude::*;
+
+use crate::agent::Action;
+use crate::core::MazeConfig;
+use crate::occupancy_grid::{CostMapConfig, LOGIT_CLAMP, OccupancyGrid};
+use crate::policies::{
+    PLANNING_COSTS, follow_path, interact, most_open_ray, move_towards, nearest_hit,
+};
+use crate::python::game_state::{AgentState, EntityType, GameState, MapMetadata};
+use crate::python::policy::Policy;
+use crate::python::summary::EvaluationSummary;
+
+/// How much one ray observation moves a cell's logits.
+const LOGIT_STEP: f32 = 0.5;
+/// Seconds between replanning the path to the nearest frontier.
+const REPLAN_INTERVAL: f32 = 0.5;
+/// Cells within this many cells of anywhere the agent has been no longer count as frontiers.
+const EXPLORED_RADIUS: i64 = 2;
+/// Half the width of the agent's collider.
+const AGENT_HALF_SIZE: f32 = 0.5;
+
+/// Maps the player grid from its own rays and drives to the nearest frontier: a known free cell
+/// next to an unknown one. Heads straight for flags and capture points its rays can see.
+#[derive(Debug, Default)]
+pub struct FrontierExplorerPolicy {
+    metadata: Option<MapMetadata>,
+    /// False when the player grid is the true grid, which must not be written to.
+    mapping: bool,
+    explored: HashSet<(usize, usize)>,
+    path: VecDeque<(f32, f32)>,
+    /// Seconds until the next replan.
+    replan_in: f32,
+}
+
+impl Policy for FrontierExplorerPolicy {
+    fn startup(
+        &mut self,
+        _state: &GameState,
+        metadata: &MapMetadata,
+        config: &MazeConfig,
+    ) -> PyResult<()> {
+        self.metadata = Some(metadata.clone());
+        self.mapping = !config.use_true_map;
+        Ok(())
+    }
+
+    fn get_action(
+        &mut self,
+        state: GameState,
+        grid: Arc<RwLock<Py<OccupancyGrid>>>,
+        elapsed: f32,
+    ) -> PyResult<(Action, Option<(f32, f32)>)> {
+        let agent = &state.agent;
+        let Some(metadata) = self.metadata.clone() else {
+            return Ok((move_towards(agent, 0.0), None));
+        };
+
+        let replanned = Python::attach(|py| {
+            let grid = grid.read().unwrap();
+            let mut grid = grid.borrow_mut(py);
+            if self.mapping {
+                integrate_rays(&mut grid, agent);
+            }
+            self.mark_explored(&grid, agent.position);
+
+            self.replan_in -= elapsed;
+            if self.path.is_empty() || self.replan_in <= 0.0 {
+                self.replan_in = REPLAN_INTERVAL;
+                Some(self.plan(&grid, agent.position))
+            } else {
+                None
+            }
+        });
+        if let Some(path) = replanned {
+            self.path = path;
+        }
+
+        if let Some(action) = interact(agent) {
+            return Ok((action, None));
+        }
+
+        let target = match agent.flag {
+            None => EntityType::Flag,
+            Some(_) => EntityType::CapturePoint,
+        };
+        if let Some(ray) = nearest_hit(&agent.raycasts, target) {
+            return Ok((move_towards(agent, ray.theta), None));
+        }
+
+        if let Some(action) = follow_path(agent, &metadata, &mut self.path) {
+            return Ok((action, None));
+        }
+
+        let heading = most_open_ray(&agent.raycasts).map_or(0.0, |ray| ray.theta);
+        Ok((move_towards(agent, heading), None))
+    }
+
+    fn on_episode_end(&mut self, _summary: EvaluationSummary) -> PyResult<()> {
+        Ok(())
+    }
+}
+
+impl FrontierExplorerPolicy {
+    fn mark_explored(&mut self, grid: &OccupancyGrid, position: (f32, f32)) {
+        let Some((col, row)) = grid.world_to_cell(position) else {
+            return;
+        };
+        for dy in -EXPLORED_RADIUS..=EXPLORED_RADIUS {
+            for dx in -EXPLORED_RADIUS..=EXPLORED_RADIUS {
+                let (x, y) = (col as i64 + dx, row as i64 + dy);
+                if x >= 0 && y >= 0 {
+                    self.explored.insert((x as usize, y as usize));
+                }
+            }
+        }
+    }
+
+    fn plan(&self, grid: &OccupancyGrid, position: (f32, f32)) -> VecDeque<(f32, f32)> {
+        let Some(start) = grid.world_to_cell(position) else {
+            return VecDeque::new();
+        };
+
+        let frontiers = grid
+            .frontier_cells()
+            .into_iter()
+            .filter(|cell| !self.explored.contains(cell))
+            .collect::<Vec<_>>();
+
+        let costs = grid.cost_map(&CostMapConfig {
+            unknown_cost: 1.0,
+            ..PLANNING_COSTS
+        });
+        grid.find_path_cells(start, &frontiers, &costs)
+            .map(|path| {
+                path.into_iter()
+                    .map(|cell| grid.cell_to_world(cell))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Updates the cells each ray passed through towards free, and the cell it hit towards wall.
+pub(crate) fn integrate_rays(grid: &mut OccupancyGrid, agent: &AgentState) {
+    let (x, y) = agent.position;
+    // Stay half a cell short of a wall hit, since the measured distance is noisy.
+    let margin = grid.cell_size * 0.5;
+
+    for ray in &agent.raycasts {
+        let along = |t: f32| (x + ray.theta.cos() * t, y + ray.theta.sin() * t);
+        let hit_wall = ray.hit == EntityType::Wall;
+
+        let wall = if hit_wall {
+            grid.world_to_cell(along(ray.distance + margin * 0.5))
+        } else {
+            None
+        };
+        let free_until = if hit_wall {
+            ray.distance - margin
+        } else {
+            ray.distance
+        };
+        if free_until > 0.0 {
+            for cell in grid.ray_cells(agent.position, along(free_until)) {
+                if Some(cell) != wall {
+                    observe(grid, cell, false);
+                }
+            }
+        }
+        if let Some(cell) = wall {
+            observe(grid, cell, true);
+        }
+    }
+
+    // Rays can slip past the corner of a wall the agent is pushing against, so trust the contact.
+    for &(nx, ny) in &agent.contact_normals {
+        let reach = AGENT_HALF_SIZE + margin * 0.5;
+        if let Some((col, row)) = grid.world_to_cell((x - nx * reach, y - ny * reach)) {
+            let width = grid.width;
+            let entry = &mut grid.grid[col + row * width];
+            entry.logit_wall = LOGIT_CLAMP;
+            entry.logit_free = -LOGIT_CLAMP;
+            entry.assignment = Some(EntityType::Wall);
+        }
+    }
+}
+
+fn observe(grid: &mut OccupancyGrid, (col, row): (usize, usize), wall: bool) {
+    let width = grid.width;
+    let entry = &mut grid.grid[col + row * width];
+    let delta = if wall { LOGIT_STEP } else { -LOGIT_STEP };
+    entry.logit_wall = (entry.logit_wall + delta).clamp(-LOGIT_CLAMP, LOGIT_CLAMP);
+    entry.logit_free = (entry.logit_free - delta).clamp(-LOGIT_CLAMP, LOGIT_CLAMP);
+    entry.assignment = Some(if entry.logit_wall > entry.logit_free {
+        EntityType::Wall
+    } else {
+        EntityType::Empty
+    });
+}
@@ -0,0 +1,119 @@
+mod frontier_explorer;
+mod oracle;
+mod random_walk;
+mod scripted;
+mod teleop;
+mod wall_follower;
+
+use std::collections::VecDeque;
+use std::f32::consts::{PI, TAU};
+
+pub use frontier_explorer::*;
+pub use oracle::*;
+pub use random_walk::*;
+pub use scripted::*;
+pub use teleop::*;
+pub use wall_follower::*;
+
+use crate::agent::{Action, BuiltinPolicy};
+use crate::flag::{CAPTURE_POINT_INTERACTION_RADIUS, FLAG_INTERACTION_RADIUS};
+use crate::occupancy_grid::{CostMapConfig, TrueGrid};
+use crate::python::game_state::{AgentState, EntityType, HitInfo, MapMetadata};
+use crate::python::policy::Policy;
+use crate::teleop::TeleopInput;
+
+/// How close a wall may get in the direction of travel before a policy turns away.
+const WALL_CLEARANCE: f32 = 3.0;
+
+/// Keeps planned paths a cell or so away from walls, so the agent does not scrape along them.
+const PLANNING_COSTS: CostMapConfig = CostMapConfig {
+    wall_threshold: 0.5,
+    inflation_radius: 1.5,
+    inflation_cost: 3.0,
+    unknown_cost: 0.0,
+};
+
+/// Builds the policy chosen in `AgentConfig.builtin_policy`.
+pub fn builtin_policy(
+    kind: BuiltinPolicy,
+    true_grid: &TrueGrid,
+    teleop: &TeleopInput,
+) -> Box<dyn Policy> {
+    match kind {
+        BuiltinPolicy::Scripted => Box::new(ScriptedPolicy::default()),
+        BuiltinPolicy::RandomWalk => Box::new(RandomWalkPolicy::default()),
+        BuiltinPolicy::WallFollower => Box::new(WallFollowerPolicy::default()),
+        BuiltinPolicy::FrontierExplorer => Box::new(FrontierExplorerPolicy::default()),
+        BuiltinPolicy::Oracle => Box::new(OraclePolicy::new(true_grid.0.clone())),
+        BuiltinPolicy::Teleop => Box::new(TeleopPolicy::new(teleop.0.clone())),
+    }
+}
+
+/// Picks up a flag, or drops the carried one, if a ray shows the target within reach.
+fn interact(agent: &AgentState) -> Option<Action> {
+    let id = agent.id;
+    match agent.flag {
+        None => nearest_hit(&agent.raycasts, EntityType::Flag)
+            .filter(|ray| ray.distance < FLAG_INTERACTION_RADIUS)
+            .map(|_| Action::PickupFlag { id }),
+        Some(_) => nearest_hit(&agent.raycasts, EntityType::CapturePoint)
+            .filter(|ray| ray.distance < CAPTURE_POINT_INTERACTION_RADIUS)
+            .map(|_| Action::DropFlag { id }),
+    }
+}
+
+/// The ray that hit an entity of type `kind` closest to the agent.
+fn nearest_hit(raycasts: &[HitInfo], kind: EntityType) -> Option<&HitInfo> {
+    raycasts
+        .iter()
+        .filter(|ray| ray.hit == kind)
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// The ray that traveled furthest.
+fn most_open_ray(raycasts: &[HitInfo]) -> Option<&HitInfo> {
+    raycasts
+        .iter()
+        .max_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// The ray pointing closest to `theta`.
+fn closest_ray(raycasts: &[HitInfo], theta: f32) -> Option<&HitInfo> {
+    raycasts
+        .iter()
+        .min_by(|a, b| angle_between(a.theta, theta).total_cmp(&angle_between(b.theta, theta)))
+}
+
+/// The absolute angle between two headings, in [0, π].
+fn angle_between(a: f32, b: f32) -> f32 {
+    ((a - b + PI).rem_euclid(TAU) - PI).abs()
+}
+
+fn move_towards(agent: &AgentState, heading: f32) -> Action {
+    Action::Move {
+        id: agent.id,
+        velocity: (
+            heading.cos() * agent.max_speed,
+            heading.sin() * agent.max_speed,
+        ),
+    }
+}
+
+/// Steers along a path of world positions, dropping the ones the agent has reached. Returns None
+/// once the path is used up.
+fn follow_path(
+    agent: &AgentState,
+    metadata: &MapMetadata,
+    path: &mut VecDeque<(f32, f32)>,
+) -> Option<Action> {
+    let (x, y) = agent.position;
+    while let Some(&(cx, cy)) = path.front() {
+        if (cx - x).hypot(cy - y) > metadata.grid_cell_size * 1.5 {
+            break;
+        }
+        path.pop_front();
+    }
+
+    let (cx, cy) = *path.front()?;
+    Some(move_towards(agent, (cy - y).atan2(cx - x)))
+}
@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use pyo3::prelude::*;
+
+use crate::agent::Action;
+use crate::core::MazeConfig;
+use crate::flag::{CAPTURE_POINT_INTERACTION_RADIUS, FLAG_INTERACTION_RADIUS};
+use crate::occupancy_grid::OccupancyGrid;
+use crate::policies::{PLANNING_COSTS, follow_path};
+use crate::python::game_state::{EntityType, GameState, MapMetadata};
+use crate::python::policy::Policy;
+use crate::python::summary::EvaluationSummary;
+
+/// Seconds between replanning.
+const REPLAN_INTERVAL: f32 = 0.25;
+/// Margin kept inside the interaction radius, so noise in the position does not cause misses.
+const REACH_MARGIN: f32 = 0.5;
+
+/// An upper baseline that cheats: it reads the true grid, plans to the nearest flag with A*,
+/// picks it up, and delivers it to the nearest capture point that does not hold a flag yet.
+pub struct OraclePolicy {
+    true_grid: Arc<RwLock<Py<OccupancyGrid>>>,
+    metadata: Option<MapMetadata>,
+    path: VecDeque<(f32, f32)>,
+    /// Seconds until the next replan.
+    replan_in: f32,
+    collected_flags: u32,
+    /// The capture point the last flag was dropped at.
+    delivering_to: Option<(f32, f32)>,
+    /// Capture points that already hold a flag.
+    occupied: Vec<(f32, f32)>,
+}
+
+impl OraclePolicy {
+    pub fn new(true_grid: Arc<RwLock<Py<OccupancyGrid>>>) -> Self {
+        Self {
+            true_grid,
+            metadata: None,
+            path: VecDeque::new(),
+            replan_in: 0.0,
+            collected_flags: 0,
+            delivering_to: None,
+            occupied: Vec::new(),
+        }
+    }
+}
+
+impl Policy for OraclePolicy {
+    fn startup(
+        &mut self,
+        _state: &GameState,
+        metadata: &MapMetadata,
+        _config: &MazeConfig,
+    ) -> PyResult<()> {
+        self.metadata = Some(metadata.clone());
+        Ok(())
+    }
+
+    fn get_action(
+        &mut self,
+        state: GameState,
+        _grid: Arc<RwLock<Py<OccupancyGrid>>>,
+        elapsed: f32,
+    ) -> PyResult<(Action, Option<(f32, f32)>)> {
+        let agent = &state.agent;
+        let id = agent.id;
+        let stop = Action::Move {
+            id,
+            velocity: (0.0, 0.0),
+        };
+        let Some(metadata) = self.metadata.clone() else {
+            return Ok((stop, None));
+        };
+
+        if state.collected_flags > self.collected_flags {
+            self.collected_flags = state.collected_flags;
+            self.occupied.extend(self.delivering_to.take());
+        }
+
+        let (target, reach) = match agent.flag {
+            None => (EntityType::Flag, FLAG_INTERACTION_RADIUS),
+            Some(_) => (EntityType::CapturePoint, CAPTURE_POINT_INTERACTION_RADIUS),
+        };
+
+        let goals = Python::attach(|py| {
+            let grid = self.true_grid.read().unwrap();
+            let grid = grid.borrow(py);
+            (0..grid.grid.len())
+                .filter(|&index| grid.grid[index].assignment == Some(target))
+                .map(|index| (index % grid.width, index / grid.width))
+                .map(|cell| (cell, grid.cell_to_world(cell)))
+                .filter(|&(_, (x, y))| {
+                    !self
+                        .occupied
+                        .iter()
+                        .any(|&(ox, oy)| (ox - x).hypot(oy - y) < CAPTURE_POINT_INTERACTION_RADIUS)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let (x, y) = agent.position;
+        let nearest = goals.iter().map(|&(_, position)| position).min_by(|a, b| {
+            (a.0 - x)
+                .hypot(a.1 - y)
+                .total_cmp(&(b.0 - x).hypot(b.1 - y))
+        });
+        if let Some(nearest) = nearest
+            && (nearest.0 - x).hypot(nearest.1 - y) < reach - REACH_MARGIN
+        {
+            self.path.clear();
+            return Ok(match agent.flag {
+                None => (Action::PickupFlag { id }, None),
+                Some(_) => {
+                    self.delivering_to = Some(nearest);
+                    (Action::DropFlag { id }, None)
+                }
+            });
+        }
+
+        self.replan_in -= elapsed;
+        if self.path.is_empty() || self.replan_in <= 0.0 {
+            self.replan_in = REPLAN_INTERVAL;
+            self.path = Python::attach(|py| {
+                let grid = self.true_grid.read().unwrap();
+                let grid = grid.borrow(py);
+                let start = grid.world_to_cell(agent.position)?;
+                let goals = goals.iter().map(|&(cell, _)| cell).collect::<Vec<_>>();
+                let path = grid.find_path_cells(start, &goals, &grid.cost_map(&PLANNING_COSTS))?;
+                Some(
+                    path.into_iter()
+                        .map(|cell| grid.cell_to_world(cell))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .map(VecDeque::from)
+            .unwrap_or_default();
+        }
+
+        let action = follow_path(agent, &metadata, &mut self.path).unwrap_or(stop);
+        Ok((action, None))
+    }
+
+    fn on_episode_end(&mut self, _summary: EvaluationSummary) -> PyResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use std::f32::consts::TAU;
+use std::sync::{Arc, RwLock};
+
+use pyo3::prelude::*;
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::agent::Action;
+use crate::core::MazeConfig;
+use crate::occupancy_grid::OccupancyGrid;
+use crate::policies::{WALL_CLEARANCE, closest_ray, interact, move_towards};
+use crate::python::game_state::{GameState, MapMetadata};
+use crate::python::policy::Policy;
+use crate::python::summary::EvaluationSummary;
+
+/// Drives in a random direction for one to three seconds, then picks another. Turns early, to a
+/// random open direction, when a wall gets close. Picks up and drops flags that happen to be in
+/// reach.
+#[derive(Debug, Default)]
+pub struct RandomWalkPolicy {
+    heading: f32,
+    /// Seconds left before picking a new heading.
+    remaining: f32,
+}
+
+impl Policy for RandomWalkPolicy {
+    fn startup(
+        &mut self,
+        _state: &GameState,
+        _metadata: &MapMetadata,
+        _config: &MazeConfig,
+    ) -> PyResult<()> {
+        Ok(())
+    }
+
+    fn get_action(
+        &mut self,
+        state: GameState,
+        _grid: Arc<RwLock<Py<OccupancyGrid>>>,
+        elapsed: f32,
+    ) -> PyResult<(Action, Option<(f32, f32)>)> {
+        let agent = &state.agent;
+        if let Some(action) = interact(agent) {
+            return Ok((action, None));
+        }
+
+        let mut rng = rand::rng();
+        self.remaining -= elapsed;
+
+        let blocked = closest_ray(&agent.raycasts, self.heading)
+            .is_some_and(|ahead| ahead.distance < WALL_CLEARANCE);
+
+        if blocked {
+            let open = agent
+                .raycasts
+                .iter()
+                .filter(|ray| ray.distance >= WALL_CLEARANCE)
+                .collect::<Vec<_>>();
+            self.heading = match open.choose(&mut rng) {
+                Some(ray) => ray.theta,
+                None => rng.random_range(0.0..TAU),
+            };
+            self.remaining = rng.random_range(1.0..3.0);
+        } else if self.remaining <= 0.0 {
+            self.heading = rng.random_range(0.0..TAU);
+            self.remaining = rng.random_range(1.0..3.0);
+        }
+
+        Ok((move_towards(agent, self.heading), None))
+    }
+
+    fn on_episode_end(&mut self, _summary: EvaluationSummary) -> PyResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::{Arc, RwLock};
+
+use pyo3::prelude::*;
+
+use crate::agent::Action;
+use crate::core::MazeConfig;
+use crate::occupancy_grid::OccupancyGrid;
+use crate::policies::{
+    WALL_CLEARANCE, closest_ray, interact, most_open_ray, move_towards, nearest_hit,
+};
+use crate::python::game_state::{EntityType, GameState, MapMetadata};
+use crate::python::policy::Policy;
+use crate::python::summary::EvaluationSummary;
+
+/// A policy that needs no Python: it heads for the nearest flag (or capture point, while
+/// carrying one) that its rays can see, picks up and drops flags within reach, and otherwise
+/// keeps going straight until a wall gets close, then turns towards the most open direction.
+#[derive(Debug, Default)]
+pub struct ScriptedPolicy {
+    heading: f32,
+}
+
+impl Policy for ScriptedPolicy {
+    fn startup(
+        &mut self,
+        _state: &GameState,
+        _metadata: &MapMetadata,
+        _config: &MazeConfig,
+    ) -> PyResult<()> {
+        Ok(())
+    }
+
+    fn get_action(
+        &mut self,
+        state: GameState,
+        _grid: Arc<RwLock<Py<OccupancyGrid>>>,
+        _elapsed: f32,
+    ) -> PyResult<(Action, Option<(f32, f32)>)> {
+        let agent = &state.agent;
+        if let Some(action) = interact(agent) {
+            return Ok((action, None));
+        }
+
+        let target = match agent.flag {
+            None => EntityType::Flag,
+            Some(_) => EntityType::CapturePoint,
+        };
+
+        if let Some(ray) = nearest_hit(&agent.raycasts, target) {
+            self.heading = ray.theta;
+        } else if let Some(ahead) = closest_ray(&agent.raycasts, self.heading)
+            && ahead.distance < WALL_CLEARANCE
+            && let Some(open) = most_open_ray(&agent.raycasts)
+        {
+            self.heading = open.theta;
+        }
+
+        Ok((move_towards(agent, self.heading), None))
+    }
+
+    fn on_episode_end(&mut self, _summary: EvaluationSummary) -> PyResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use serde::Serialize;
+
+use crate::agent::Action;
+use crate::core::MazeConfig;
+use crate::occupancy_grid::OccupancyGrid;
+use crate::policies::integrate_rays;
+use crate::python::game_state::{GameState, MapMetadata};
+use crate::python::policy::Policy;
+use crate::python::summary::EvaluationSummary;
+use crate::teleop::TeleopCommand;
+
+/// Does what the keyboard says, at the agent's top speed, while mapping the player grid from its
+/// rays. Optionally writes every step to `AgentConfig.demonstration_path`.
+#[derive(Debug)]
+pub struct TeleopPolicy {
+    input: Arc<Mutex<TeleopCommand>>,
+    /// False when the player grid is the true grid, which must not be written to.
+    mapping: bool,
+    demonstration: Option<BufWriter<File>>,
+    started: Instant,
+}
+
+/// One line of a demonstration file.
+#[derive(Serialize)]
+struct DemonstrationStep<'a> {
+    /// Seconds since the policy started.
+    time: f32,
+    state: &'a GameState,
+    action: &'a Action,
+}
+
+impl TeleopPolicy {
+    pub fn new(input: Arc<Mutex<TeleopCommand>>) -> Self {
+        Self {
+            input,
+            mapping: false,
+            demonstration: None,
+            started: Instant::now(),
+        }
+    }
+
+    fn choose_action(&self, state: &GameState) -> Action {
+        let id = state.agent.id;
+        let mut command = self.input.lock().unwrap();
+        if std::mem::take(&mut command.pickup) {
+            return Action::PickupFlag { id };
+        }
+        if std::mem::take(&mut command.drop) {
+            return Action::DropFlag { id };
+        }
+        let velocity = command.direction * state.agent.max_speed;
+        Action::Move {
+            id,
+            velocity: velocity.into(),
+        }
+    }
+}
+
+impl Policy for TeleopPolicy {
+    fn startup(
+        &mut self,
+        _state: &GameState,
+        _metadata: &MapMetadata,
+        config: &MazeConfig,
+    ) -> PyResult<()> {
+        self.mapping = !config.use_true_map;
+        self.started = Instant::now();
+        if let Some(path) = &config.agent.demonstration_path {
+            let path = std::path::Path::new(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            self.demonstration = Some(BufWriter::new(File::create(path)?));
+        }
+        Ok(())
+    }
+
+    fn get_action(
+        &mut self,
+        state: GameState,
+        grid: Arc<RwLock<Py<OccupancyGrid>>>,
+        _elapsed: f32,
+    ) -> PyResult<(Action, Option<(f32, f32)>)> {
+        if self.mapping {
+            Python::attach(|py| {
+                let grid = grid.read().unwrap();
+                integrate_rays(&mut grid.borrow_mut(py), &state.agent);
+            });
+        }
+
+        let action = self.choose_action(&state);
+
+        if let Some(demonstration) = &mut self.demonstration {
+            let step = DemonstrationStep {
+                time: self.started.elapsed().as_secs_f32(),
+                state: &state,
+                action: &action,
+            };
+            serde_json::to_writer(&mut *demonstration, &step).map_err(|e| {
+                PyRuntimeError::new_err(format!("Failed to write demonstration: {}", e))
+            })?;
+            demonstration.write_all(b"\n")?;
+        }
+
+        Ok((action, None))
+    }
+
+    fn on_episode_end(&mut self, _summary: EvaluationSummary) -> PyResult<()> {
+        if let Some(mut demonstration) = self.demonstration.take() {
+            demonstration.flush()?;
+        }
+        Ok(())
+    }
+}
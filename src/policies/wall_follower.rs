@@ -0,0 +1,100 @@
+use std::f32::consts::FRAC_PI_2;
+use std::sync::{Arc, RwLock};
+
+use pyo3::prelude::*;
+
+use crate::agent::Action;
+use crate::core::MazeConfig;
+use crate::occupancy_grid::OccupancyGrid;
+use crate::policies::{WALL_CLEARANCE, closest_ray, interact, move_towards};
+use crate::python::game_state::{GameState, MapMetadata};
+use crate::python::policy::Policy;
+use crate::python::summary::EvaluationSummary;
+use crate::scene::WALL_THICKNESS;
+
+/// Follows the right-hand rule: keeps a wall on its right, turns right into every opening and
+/// left when the way ahead is blocked. Headings are always one of the four grid directions; the
+/// right hand side is `heading + π/2`, since y points down the screen.
+#[derive(Debug)]
+pub struct WallFollowerPolicy {
+    heading: f32,
+    /// Approximate distance driven so far, used to time turns.
+    travelled: f32,
+    /// Distance at which to make a pending right turn.
+    turn_at: Option<f32>,
+    /// No new right turns are started before this distance.
+    cooldown_until: f32,
+    corridor_width: f32,
+}
+
+impl Default for WallFollowerPolicy {
+    fn default() -> Self {
+        Self {
+            heading: 0.0,
+            travelled: 0.0,
+            turn_at: None,
+            cooldown_until: 0.0,
+            corridor_width: 5.0,
+        }
+    }
+}
+
+impl Policy for WallFollowerPolicy {
+    fn startup(
+        &mut self,
+        _state: &GameState,
+        metadata: &MapMetadata,
+        _config: &MazeConfig,
+    ) -> PyResult<()> {
+        self.corridor_width = metadata.maze_cell_size;
+        Ok(())
+    }
+
+    fn get_action(
+        &mut self,
+        state: GameState,
+        _grid: Arc<RwLock<Py<OccupancyGrid>>>,
+        elapsed: f32,
+    ) -> PyResult<(Action, Option<(f32, f32)>)> {
+        let agent = &state.agent;
+        if let Some(action) = interact(agent) {
+            return Ok((action, None));
+        }
+
+        self.travelled += agent.max_speed * elapsed;
+
+        let ahead = closest_ray(&agent.raycasts, self.heading).map(|ray| ray.distance);
+        let right = closest_ray(&agent.raycasts, self.heading + FRAC_PI_2).map(|ray| ray.distance);
+
+        if let Some(turn_at) = self.turn_at {
+            if self.travelled >= turn_at {
+                self.heading += FRAC_PI_2;
+                self.turn_at = None;
+                self.cooldown_until = self.travelled + self.corridor_width;
+            }
+        } else if ahead.is_some_and(|distance| distance < WALL_CLEARANCE) {
+            self.heading -= FRAC_PI_2;
+            self.cooldown_until = self.travelled + 1.0;
+        } else if right.is_some_and(|distance| distance > self.corridor_width)
+            && self.travelled >= self.cooldown_until
+        {
+            // Drive on into the middle of the opening before turning.
+            self.turn_at = Some(self.travelled + self.corridor_width * 0.5);
+        }
+
+        // Nudge towards the middle of the corridor, as seen from the wall on the right.
+        let mut heading = self.heading;
+        if let Some(right) = right
+            && right < self.corridor_width
+        {
+            let ideal = (self.corridor_width - WALL_THICKNESS) * 0.5;
+            heading += ((right - ideal) * 0.2).clamp(-0.3, 0.3);
+        }
+
+        Ok((move_towards(agent, heading), None))
+    }
+
+    fn on_episode_end(&mut self, _summary: EvaluationSummary) -> PyResult<()> {
+        Ok(())
+    }
+}
@@ -1,22 +1,28 @@
+use std::collections::{BTreeMap, HashMap};
+
 use avian3d::prelude::*;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use numpy::{PyArray1, PyArray3, PyArrayMethods};
 use pyo3::prelude::*;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_enum, gen_stub_pymethods};
 use rand::rng;
 use rand_distr::Distribution;
 use rand_distr::Normal;
+use serde::{Deserialize, Serialize};
 
-use crate::agent::AGENT_RAYCAST_MAX_DISTANCE;
+use crate::agent::{AGENT_RAYCAST_MAX_DISTANCE, Action};
 use crate::core::MazeConfig;
+use crate::recording::ImageObservation;
 
 use crate::{
-    agent::{Agent, RayCasters},
+    agent::{Agent, CollisionReport, RayCasters},
     character_controller::MaxLinearSpeed,
-    flag::{CapturePoint, Flag},
+    flag::{CapturePoint, Flag, FlagStatus},
     scene::Wall,
 };
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 #[gen_stub_pyclass]
 #[pyclass(name = "GameState", frozen)]
 pub struct GameState {
@@ -26,13 +32,245 @@ pub struct GameState {
     pub total_flags: u32,
     #[pyo3(get)]
     pub collected_flags: u32,
+    /// Flags that have not been captured yet, `total_flags - collected_flags`.
+    #[pyo3(get)]
+    pub remaining_flags: u32,
+    /// The flags the agent has seen or carried so far, ordered by id, with their status and
+    /// position when last observed. Positions are where the agent placed them, from its noisy
+    /// pose and range readings.
+    #[pyo3(get)]
+    pub flags: Vec<FlagInfo>,
+    /// The capture points the agent has seen so far, ordered by id, placed like `flags`.
+    #[pyo3(get)]
+    pub capture_points: Vec<CapturePointInfo>,
+    /// Size of the world along x, in world units. Same as `metadata.world_width`.
     #[pyo3(get)]
     pub world_width: f32,
+    /// Size of the world along y, in world units. Same as `metadata.world_height`.
     #[pyo3(get)]
     pub world_height: f32,
+    /// Fixed facts about the map: its size, origin and cell sizes.
+    #[pyo3(get)]
+    pub metadata: MapMetadata,
+    /// The shaped reward earned since the previous state, see `RewardConfig`.
+    #[pyo3(get)]
+    pub reward: f32,
+    /// The sum of all rewards earned so far this episode.
+    #[pyo3(get)]
+    pub cumulative_reward: f32,
+    /// The outcome of the last action applied since the previous state, if any.
+    #[pyo3(get)]
+    pub action_result: Option<ActionResult>,
+
+    /// Ground truth about the agent and the map. None unless `MazeConfig.privileged_info` is set.
+    #[pyo3(get)]
+    pub privileged_info: Option<PrivilegedInfo>,
+
+    /// Exposed to Python as a NumPy array by `image`. Not sent to policy servers.
+    #[serde(skip)]
+    pub image: Option<ImageObservation>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl GameState {
+    /// A top-down picture of the map around the agent as a (height, width, 4) RGBA uint8
+    /// array, in the same colors as the grid overlay in the window. None unless
+    /// `MazeConfig.image_observation.enabled` is set.
+    #[getter]
+    fn image<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyArray3<u8>>>> {
+        // Raise an ImportError without NumPy, where the numpy crate would panic.
+        py.import("numpy")?;
+        self.image
+            .as_ref()
+            .map(|image| {
+                PyArray1::from_slice(py, &image.pixels).reshape([image.height, image.width, 4])
+            })
+            .transpose()
+    }
+}
+
+/// Ground truth that the agent's sensors do not give it, see `MazeConfig.privileged_info`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[gen_stub_pyclass]
+#[pyclass(name = "PrivilegedInfo", frozen)]
+pub struct PrivilegedInfo {
+    /// The agent's state without noise: its true position and the true ray distances.
+    #[pyo3(get)]
+    pub agent: AgentState,
+
+    /// Every flag in the map, including carried and captured ones.
+    #[pyo3(get)]
+    pub flags: Vec<FlagInfo>,
+
+    /// Every capture point in the map.
+    #[pyo3(get)]
+    pub capture_points: Vec<CapturePointInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[gen_stub_pyclass]
+#[pyclass(name = "FlagInfo", frozen, str)]
+pub struct FlagInfo {
+    /// The entity ID of the flag, as in `AgentState.flag` and the `PickupFlag` action.
+    #[pyo3(get)]
+    pub id: u32,
+
+    #[pyo3(get)]
+    pub status: FlagStatus,
+
+    /// The position of the flag in world coordinates.
+    #[pyo3(get)]
+    pub position: (f32, f32),
+}
+
+impl std::fmt::Display for FlagInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FlagInfo(id={}, status={:?}, position=({:.2}, {:.2}))",
+            self.id, self.status, self.position.0, self.position.1
+        )
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[gen_stub_pyclass]
+#[pyclass(name = "CapturePointInfo", frozen, str)]
+pub struct CapturePointInfo {
+    /// The entity ID of the capture point.
+    #[pyo3(get)]
+    pub id: u32,
+
+    /// The position of the capture point in world coordinates.
+    #[pyo3(get)]
+    pub position: (f32, f32),
+}
+
+impl std::fmt::Display for CapturePointInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "CapturePointInfo(id={}, position=({:.2}, {:.2}))",
+            self.id, self.position.0, self.position.1
+        )
+    }
+}
+
+/// The flags and capture points the agent knows about, as of when it last observed them.
+#[derive(Resource, Default)]
+pub struct ObservedObjects {
+    flags: BTreeMap<u32, FlagInfo>,
+    capture_points: BTreeMap<u32, CapturePointInfo>,
+    /// The flag the agent carried as of the last observation. It knows where it let go of it.
+    carried: Option<Entity>,
+}
+
+/// A flag or capture point hit by a ray, and where the agent would place the hit from its noisy
+/// pose and the ray's noisy range.
+#[derive(Debug, Clone, Copy)]
+pub struct Sighting {
+    pub entity: Entity,
+    pub position: Vec2,
+}
+
+impl ObservedObjects {
+    pub fn flags(&self) -> Vec<FlagInfo> {
+        self.flags.values().cloned().collect()
+    }
+
+    pub fn capture_points(&self) -> Vec<CapturePointInfo> {
+        self.capture_points.values().cloned().collect()
+    }
+}
+
+/// The flags and capture points in the world, for building `GameState`.
+#[derive(SystemParam)]
+pub struct MapObjects<'w, 's> {
+    pub flags: Query<'w, 's, (Entity, &'static Flag, &'static GlobalTransform)>,
+    pub capture_points: Query<'w, 's, (Entity, &'static GlobalTransform), With<CapturePoint>>,
+}
+
+impl MapObjects<'_, '_> {
+    pub fn flag_infos(&self) -> Vec<FlagInfo> {
+        self.flags
+            .iter()
+            .map(|(entity, flag, transform)| flag_info(entity, flag, transform.translation().xz()))
+            .collect()
+    }
+
+    pub fn capture_point_infos(&self) -> Vec<CapturePointInfo> {
+        self.capture_points
+            .iter()
+            .map(|(entity, transform)| capture_point_info(entity, transform.translation().xz()))
+            .collect()
+    }
+
+    /// Updates `observed` with the objects in `seen` and the flag being `carried`, which the
+    /// agent places at its own noisy `agent_position`. Everything else keeps its last observed
+    /// position.
+    pub fn observe(
+        &self,
+        observed: &mut ObservedObjects,
+        seen: &[Sighting],
+        carried: Option<Entity>,
+        agent_position: Vec2,
+    ) {
+        // Several rays may hit the same object, so place it at the mean of their hits.
+        let mut hits = HashMap::<Entity, (Vec2, f32)>::new();
+        for sighting in seen {
+            let (sum, count) = hits.entry(sighting.entity).or_default();
+            *sum += sighting.position;
+            *count += 1.0;
+        }
+        let mut sightings = hits
+            .into_iter()
+            .map(|(entity, (sum, count))| (entity, sum / count))
+            .collect::<Vec<_>>();
+        sightings.extend(carried.map(|entity| (entity, agent_position)));
+
+        for (entity, position) in sightings {
+            if let Ok((entity, flag, _)) = self.flags.get(entity) {
+                observed
+                    .flags
+                    .insert(entity.index(), flag_info(entity, flag, position));
+            }
+            if let Ok((entity, _)) = self.capture_points.get(entity) {
+                observed
+                    .capture_points
+                    .insert(entity.index(), capture_point_info(entity, position));
+            }
+        }
+
+        // The agent knows whether it dropped or captured the flag it let go of, but not where
+        // it went after that.
+        if observed.carried != carried
+            && let Some(released) = observed.carried
+            && let Ok((_, flag, _)) = self.flags.get(released)
+            && let Some(info) = observed.flags.get_mut(&released.index())
+        {
+            info.status = flag.status;
+        }
+        observed.carried = carried;
+    }
+}
+
+fn flag_info(entity: Entity, flag: &Flag, position: Vec2) -> FlagInfo {
+    FlagInfo {
+        id: entity.index(),
+        status: flag.status,
+        position: position.into(),
+    }
+}
+
+fn capture_point_info(entity: Entity, position: Vec2) -> CapturePointInfo {
+    CapturePointInfo {
+        id: entity.index(),
+        position: position.into(),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 #[gen_stub_pyclass]
 #[pyclass(name = "AgentState", frozen)]
 pub struct AgentState {
@@ -59,12 +297,25 @@ pub struct AgentState {
     /// The maximum linear speed of the agent.
     #[pyo3(get)]
     pub max_speed: f32,
+
+    /// Whether the agent is currently touching a wall.
+    #[pyo3(get)]
+    pub colliding: bool,
+
+    /// The normals of the wall contacts in world coordinates, pointing from the wall towards the agent.
+    #[pyo3(get)]
+    pub contact_normals: Vec<(f32, f32)>,
+
+    /// How many times the agent ran into a wall since the previous state.
+    #[pyo3(get)]
+    pub impacts: u32,
 }
 
 #[gen_stub_pyclass_enum]
 #[pyclass(name = "EntityType", frozen)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
 /// The type of entity that was hit by a raycast. Note, that "Unknown" should not occur.
+#[serde(rename_all = "snake_case")]
 pub enum EntityType {
     Wall,
     Empty,
@@ -86,9 +337,140 @@ impl std::fmt::Display for EntityType {
     }
 }
 
+#[gen_stub_pyclass_enum]
+#[pyclass(name = "ActionStatus", frozen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Serialize)]
+/// Whether an action was applied as requested, applied after being adjusted, or not applied at all.
+#[serde(rename_all = "snake_case")]
+pub enum ActionStatus {
+    Accepted,
+    Clamped,
+    Rejected,
+}
+
+#[gen_stub_pyclass]
+#[pyclass(name = "ActionResult", frozen, str)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ActionResult {
+    /// The action that was submitted by the policy.
+    #[pyo3(get)]
+    pub action: Action,
+
+    /// How the simulation handled the action.
+    #[pyo3(get)]
+    pub status: ActionStatus,
+
+    /// Why the action was clamped or rejected. None if it was accepted.
+    #[pyo3(get)]
+    pub reason: Option<String>,
+}
+
+impl ActionResult {
+    pub fn accepted(action: Action) -> Self {
+        Self {
+            action,
+            status: ActionStatus::Accepted,
+            reason: None,
+        }
+    }
+
+    pub fn clamped(action: Action, reason: impl Into<String>) -> Self {
+        Self {
+            action,
+            status: ActionStatus::Clamped,
+            reason: Some(reason.into()),
+        }
+    }
+
+    pub fn rejected(action: Action, reason: impl Into<String>) -> Self {
+        Self {
+            action,
+            status: ActionStatus::Rejected,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+impl std::fmt::Display for ActionResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ActionResult(action={:?}, status={:?}, reason={:?})",
+            self.action, self.status, self.reason
+        )
+    }
+}
+
+/// Fixed facts about the map, handed to the policy's `startup` hook.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[gen_stub_pyclass]
+#[pyclass(name = "MapMetadata", frozen, str)]
+pub struct MapMetadata {
+    /// Size of the world along x, in world units. The world is centered on the origin.
+    #[pyo3(get)]
+    pub world_width: f32,
+
+    /// Size of the world along z (y in `GameState` coordinates), in world units.
+    #[pyo3(get)]
+    pub world_height: f32,
+
+    /// Width of the maze corridors, in world units.
+    #[pyo3(get)]
+    pub maze_cell_size: f32,
+
+    /// Size of each occupancy grid cell, in world units.
+    #[pyo3(get)]
+    pub grid_cell_size: f32,
+
+    /// Number of occupancy grid cells in the x direction.
+    #[pyo3(get)]
+    pub grid_width: usize,
+
+    /// Number of occupancy grid cells in the y direction.
+    #[pyo3(get)]
+    pub grid_height: usize,
+
+    /// World coordinates of the corner of occupancy grid cell (0, 0).
+    #[pyo3(get)]
+    pub origin: (f32, f32),
+}
+
+impl MapMetadata {
+    pub fn from_config(config: &MazeConfig) -> Self {
+        let maze = &config.maze_generation;
+        let (grid_width, grid_height) = config.occupancy_grid_size();
+
+        Self {
+            world_width: maze.width,
+            world_height: maze.height,
+            maze_cell_size: maze.cell_size,
+            grid_cell_size: config.agent.occupancy_grid_cell_size,
+            grid_width,
+            grid_height,
+            origin: maze.origin().into(),
+        }
+    }
+}
+
+impl std::fmt::Display for MapMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MapMetadata(world={}x{}, maze_cell_size={}, grid={}x{} @ {}, origin={:?})",
+            self.world_width,
+            self.world_height,
+            self.maze_cell_size,
+            self.grid_width,
+            self.grid_height,
+            self.grid_cell_size,
+            self.origin
+        )
+    }
+}
+
 #[gen_stub_pyclass]
 #[pyclass(name = "HitInfo", frozen, str)]
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct HitInfo {
     /// The angle of the raycast in radians, relative to the +x axis (right on the screen). Remember, +y points down on the screen!
     #[pyo3(get)]
@@ -118,7 +500,7 @@ pub struct HitInfo {
 
 #[gen_stub_pyclass]
 #[pyclass(name = "SensorConfidence")]
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct SensorConfidence {
     /// Probability of being free space
     #[pyo3(get)]
@@ -176,7 +558,7 @@ impl std::fmt::Display for HitInfo {
     }
 }
 
-fn classify(
+pub fn classify(
     e: Entity,
     kinds: &Query<(Option<&Wall>, Option<&Flag>, Option<&CapturePoint>)>,
 ) -> EntityType {
@@ -212,6 +594,8 @@ fn confidence_by_entity_type(entity_type: EntityType) -> SensorConfidence {
     }
 }
 
+/// Reads the agent's sensors, returning its noisy state, its true state, and the flags and
+/// capture points its rays hit.
 #[allow(clippy::type_complexity)]
 pub fn collect_agent_state(
     config: &MazeConfig,
@@ -227,7 +611,8 @@ pub fn collect_agent_state(
         With<Agent>,
     >,
     kinds: &Query<(Option<&Wall>, Option<&Flag>, Option<&CapturePoint>)>,
-) -> (AgentState, AgentState) {
+    collisions: &CollisionReport,
+) -> (AgentState, AgentState, Vec<Sighting>) {
     let (entity, max_speed, agent_transform, raycasters, children) =
         agent.single().expect("There should be exactly one agent");
 
@@ -239,6 +624,8 @@ pub fn collect_agent_state(
         })
     });
 
+    // The flags and capture points hit, with the angle of the ray that hit them.
+    let mut hits = Vec::new();
     let mut raycasts = raycasters
         .0
         .iter()
@@ -257,6 +644,14 @@ pub fn collect_agent_state(
             let entity_type = hit
                 .map(|hit| classify(hit.entity, kinds))
                 .unwrap_or(EntityType::Empty);
+            if let Some(hit) = hit
+                && matches!(entity_type, EntityType::Flag | EntityType::CapturePoint)
+            {
+                hits.push((
+                    hit.entity,
+                    raycaster.direction.z.atan2(raycaster.direction.x),
+                ));
+            }
 
             let distance = hit
                 .map(|hit| hit.distance)
@@ -286,6 +681,9 @@ pub fn collect_agent_state(
         raycasts,
         flag: flag.map(|f| f.index()),
         max_speed: max_speed.0,
+        colliding: collisions.touching,
+        contact_normals: collisions.normals.iter().map(|n| (n.x, n.y)).collect(),
+        impacts: collisions.impacts,
     };
 
     let noisy_agent_state = AgentState {
@@ -305,8 +703,20 @@ pub fn collect_agent_state(
                 ..hit_info
             })
             .collect::<Vec<_>>(),
-        ..true_agent_state
+        ..true_agent_state.clone()
     };
 
-    (noisy_agent_state, true_agent_state)
+    let seen = hits
+        .into_iter()
+        .filter_map(|(entity, theta)| {
+            let ray = (noisy_agent_state.raycasts.iter()).find(|ray| ray.theta == theta)?;
+            Some(Sighting {
+                entity,
+                position: Vec2::from(noisy_agent_state.position)
+                    + Vec2::from_angle(theta) * ray.distance,
+            })
+        })
+        .collect();
+
+    (noisy_agent_state, true_agent_state, seen)
 }
@@ -1,3 +1,5 @@
 pub mod game_state;
 pub mod policy;
+pub mod remote_policy;
 pub mod state_queue;
+pub mod summary;
@@ -1,19 +1,34 @@
-use std::sync::{Arc, RwLock};
-use std::time::Instant;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use avian3d::prelude::SpatialQuery;
 use bevy::prelude::*;
-use crossbeam_channel::{Receiver, Sender, TrySendError};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TrySendError};
+use pyo3::call::PyCallArgs;
 use pyo3::prelude::*;
 
-use crate::agent::{GhostAgent, RayCasters};
+use crate::agent::{
+    ActionResultMessage, BudgetOverrunMode, CollisionReport, EstimateConsistency, GhostAgent,
+    PolicyErrorMode, PoseUncertainty, RayCasters,
+};
 use crate::character_controller::MaxLinearSpeed;
+use crate::debug_draw::{DebugDraw, DebugShape, DebugShapes};
 use crate::flag::{CapturePoint, Flag, FlagCaptureCounts};
 use crate::interaction_range::{FlagDropMessage, FlagPickupMessage};
+use crate::occupancy_grid::{ExplorationTracker, PlayerGrid, TrueGrid};
 use crate::occupancy_grid::{OccupancyGrid, OccupancyGridView};
-use crate::occupancy_grid::{PlayerGrid, TrueGrid};
-use crate::python::game_state::collect_agent_state;
+use crate::policies::builtin_policy;
+use crate::python::game_state::{
+    ActionResult, MapMetadata, MapObjects, ObservedObjects, PrivilegedInfo, collect_agent_state,
+};
+use crate::python::remote_policy::RemotePolicy;
+use crate::python::summary::{EvaluationSummary, PolicyStats, collect_summary};
+use crate::recording::{FrameObjects, GridOverlay, ImageObservation};
+use crate::reward::RewardTracker;
 use crate::scene::{EstimatedPositionText, Wall};
+use crate::teleop::TeleopInput;
 use crate::{
     agent::{Action, Agent},
     character_controller::MovementMessage,
@@ -21,18 +36,47 @@ use crate::{
     python::game_state::GameState,
 };
 
+/// How long to wait on exit for the policy to finish its current call and `on_episode_end`.
+const WORKER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+/// With `PolicyErrorMode::Restart`, how many failed `startup` calls in a row stop the simulation.
+const MAX_STARTUP_ATTEMPTS: u32 = 8;
+/// The wait after the first failed `startup`, doubled after each further failure.
+const STARTUP_RETRY_DELAY: Duration = Duration::from_millis(100);
+
 #[derive(Resource)]
 struct Bridge {
     pub agent_bridge: PolicyBridge,
     pub test_bridge: Option<TestHarnessBridge>,
 }
 
+#[allow(clippy::type_complexity)]
 struct PolicyBridge {
-    pub tx_state: Sender<(GameState, Arc<RwLock<Py<OccupancyGrid>>>)>,
+    /// The state for the policy, its grid, and the true agent position the state was built at.
+    pub tx_state: Sender<(GameState, Arc<RwLock<Py<OccupancyGrid>>>, Vec2)>,
     pub rx_action: Receiver<Action>,
-    pub rx_position: Receiver<(f32, f32)>,
+    /// Each estimated position, with the true position of the state it was estimated from.
+    pub rx_position: Receiver<((f32, f32), Vec2)>,
+    /// What the policy drew with its `DebugDraw` during each call.
+    pub rx_debug: Receiver<Vec<DebugShape>>,
+    /// The particles and covariance behind each reported position.
+    pub rx_uncertainty: Receiver<PoseUncertainty>,
+    pub rx_error: Receiver<PyErr>,
+    /// How long each `get_action` call took.
+    pub rx_latency: Receiver<Duration>,
+    pub budget: Option<Duration>,
+    /// Hands the end-of-run summary to the worker for the policy's `on_episode_end` hook.
+    pub tx_episode_end: Sender<EvaluationSummary>,
+    pub worker: Option<JoinHandle<()>>,
+    /// Never receives anything, but disconnects once the worker thread has finished.
+    pub rx_worker_done: Receiver<()>,
 }
 
+/// Holds the exception that stopped the simulation, so it can be re-raised on the Python side.
+pub type PolicyErrorSlot = Arc<Mutex<Option<PyErr>>>;
+
+#[derive(Resource)]
+struct PolicyError(PolicyErrorSlot);
+
 #[derive(Clone)]
 #[allow(clippy::type_complexity)]
 pub struct TestHarnessBridge {
@@ -42,15 +86,23 @@ pub struct TestHarnessBridge {
         Arc<RwLock<Py<OccupancyGrid>>>,
     )>,
     pub rx_stop: Receiver<()>,
+    pub tx_summary: Sender<EvaluationSummary>,
 }
 
 #[derive(Resource)]
 struct PolicyTimer(Timer);
 
+/// The most recent action outcome per agent id, handed to the policy with the next state.
+#[derive(Resource, Default)]
+struct LastActionResult(HashMap<u32, ActionResult>);
+
 pub struct PythonPolicyBridgePlugin {
     pub config: MazeConfig,
-    pub agent_policy: Py<PyAny>,
+    /// The Python policy to call. Ignored when `AgentConfig.policy_server` is set; when neither
+    /// is given, `AgentConfig.builtin_policy` drives the agent.
+    pub agent_policy: Option<Py<PyAny>>,
     pub test_harness: Option<TestHarnessBridge>,
+    pub policy_error: PolicyErrorSlot,
 }
 
 impl Plugin for PythonPolicyBridgePlugin {
@@ -58,69 +110,210 @@ impl Plugin for PythonPolicyBridgePlugin {
         let hz = self.config.agent.policy_hz.clamp(1.0, 240.0);
         let interval = 1.0_f32 / hz;
 
-        let agent_bridge = Python::attach(|py| {
-            PolicyBridge::start(self.agent_policy.clone_ref(py))
-                .expect("Failed to start agent policy")
-        });
-
         app.insert_resource(PolicyTimer(Timer::from_seconds(
             interval,
             TimerMode::Repeating,
         )));
 
-        app.insert_resource(Bridge {
-            agent_bridge,
-            test_bridge: self.test_harness.clone(),
-        });
+        app.init_resource::<LastActionResult>();
+        app.init_resource::<ObservedObjects>();
+        app.init_resource::<PolicyStats>();
+        app.insert_resource(PolicyError(self.policy_error.clone()));
 
         app.add_systems(
             Update,
             (
                 send_game_states,
                 apply_actions,
+                record_action_results,
+                stop_on_policy_error,
+                record_policy_latency,
                 update_estimated_position_text,
+                update_debug_shapes,
                 on_test_harness_stop,
             ),
         );
 
-        app.add_systems(Last, shutdown_workers_on_exit);
+        app.add_systems(
+            Last,
+            (report_summary_on_exit, shutdown_workers_on_exit).chain(),
+        );
+    }
+
+    // The bridge starts once every plugin is built, since built-in policies may need the grids.
+    fn finish(&self, app: &mut App) {
+        let policy: Box<dyn Policy> = match &self.config.agent.policy_server {
+            Some(address) => Box::new(RemotePolicy::new(address.clone())),
+            None => match &self.agent_policy {
+                Some(policy) => {
+                    match PythonPolicy::new(policy, app.world().resource::<PlayerGrid>()) {
+                        Ok(policy) => Box::new(policy),
+                        Err(err) => {
+                            // Without a bridge the systems idle until the app exits, and `run()`
+                            // re-raises the error.
+                            *self.policy_error.lock().unwrap() = Some(err);
+                            app.world_mut().write_message(AppExit::error());
+                            return;
+                        }
+                    }
+                }
+                None => builtin_policy(
+                    self.config.agent.builtin_policy,
+                    app.world().resource::<TrueGrid>(),
+                    app.world().resource::<TeleopInput>(),
+                ),
+            },
+        };
+        let agent_bridge =
+            PolicyBridge::start(policy, &self.config).expect("Failed to start agent policy");
+
+        app.insert_resource(Bridge {
+            agent_bridge,
+            test_bridge: self.test_harness.clone(),
+        });
     }
 }
 
 impl PolicyBridge {
-    pub fn start(policy: Py<PyAny>) -> anyhow::Result<Self> {
+    pub fn start(mut policy: Box<dyn Policy>, config: &MazeConfig) -> anyhow::Result<Self> {
+        let on_error = config.agent.on_policy_error;
+        let on_overrun = config.agent.on_budget_overrun;
+        // Room for a single state: while the worker is busy, no new states are built instead
+        // of queueing up behind it.
         let (tx_state, rx_state) =
-            crossbeam_channel::bounded::<(GameState, Arc<RwLock<Py<OccupancyGrid>>>)>(60);
+            crossbeam_channel::bounded::<(GameState, Arc<RwLock<Py<OccupancyGrid>>>, Vec2)>(1);
         let (tx_action, rx_action) = crossbeam_channel::bounded::<Action>(60);
-        let (tx_position, rx_position) = crossbeam_channel::bounded::<(f32, f32)>(60);
-
-        std::thread::spawn(move || {
+        let (tx_position, rx_position) = crossbeam_channel::bounded::<((f32, f32), Vec2)>(60);
+        let (tx_debug, rx_debug) = crossbeam_channel::bounded::<Vec<DebugShape>>(60);
+        let (tx_uncertainty, rx_uncertainty) = crossbeam_channel::bounded::<PoseUncertainty>(60);
+        let (tx_error, rx_error) = crossbeam_channel::bounded::<PyErr>(1);
+        let (tx_latency, rx_latency) = crossbeam_channel::bounded::<Duration>(60);
+        let (tx_episode_end, rx_episode_end) = crossbeam_channel::bounded::<EvaluationSummary>(1);
+        let (tx_worker_done, rx_worker_done) = crossbeam_channel::bounded::<()>(0);
+        let budget = config.agent.policy_budget();
+
+        let metadata = MapMetadata::from_config(config);
+        let config = config.clone();
+
+        let worker = std::thread::spawn(move || {
+            let _done = tx_worker_done;
             let mut start = Instant::now();
-            while let Ok((state, grid)) = rx_state.recv() {
-                let elapsed = start.elapsed();
-                let action_and_position = Python::attach(|py| -> PyResult<(Action, (f32, f32))> {
-                    let state = Py::new(py, state)?;
-                    let grid = Py::new(py, OccupancyGridView { inner: grid })?;
-                    let action: Action = policy
-                        .call_method(py, "get_action", (state, grid, elapsed.as_secs_f32()), None)?
-                        .extract(py)?;
+            let mut last_on_time: Option<Action> = None;
+            let mut started_up = false;
+            let mut startup_failures = 0;
+            let mut retry_at = Instant::now();
+            loop {
+                let (state, grid, true_position) = crossbeam_channel::select! {
+                    recv(rx_state) -> next => match next {
+                        Ok(next) => next,
+                        Err(_) => break, // main thread has exited
+                    },
+                    recv(rx_episode_end) -> summary => {
+                        if let Ok(summary) = summary
+                            && let Err(e) = policy.on_episode_end(summary)
+                        {
+                            error!("Error calling policy on_episode_end: {}", format_py_err(&e));
+                        }
+                        break;
+                    },
+                };
+
+                if !started_up {
+                    // States that arrive while backing off from a failed startup are dropped.
+                    if Instant::now() < retry_at {
+                        continue;
+                    }
+                    started_up = true;
+                    match policy.startup(&state, &metadata, &config) {
+                        Ok(()) => startup_failures = 0,
+                        Err(e) => {
+                            error!("Error calling policy startup: {}", format_py_err(&e));
+                            match on_error {
+                                PolicyErrorMode::Stop => {
+                                    let _ = tx_error.send(e);
+                                    break;
+                                }
+                                PolicyErrorMode::Restart => {
+                                    startup_failures += 1;
+                                    if startup_failures >= MAX_STARTUP_ATTEMPTS {
+                                        error!(
+                                            "Policy startup failed {} times in a row, stopping",
+                                            startup_failures
+                                        );
+                                        let _ = tx_error.send(e);
+                                        break;
+                                    }
+                                    // Try again with a later state.
+                                    retry_at = Instant::now()
+                                        + STARTUP_RETRY_DELAY * 2u32.pow(startup_failures - 1);
+                                    started_up = false;
+                                    continue;
+                                }
+                                PolicyErrorMode::NoOp => {}
+                            }
+                        }
+                    }
+                    start = Instant::now();
+                }
 
-                    let position: (f32, f32) = policy.getattr(py, "position")?.extract(py)?;
-                    Ok((action, position))
-                });
+                let elapsed = start.elapsed();
+                let agent_id = state.agent.id;
+                let call_start = Instant::now();
+                let action_and_position = policy.get_action(state, grid, elapsed.as_secs_f32());
+                let latency = policy
+                    .take_latency()
+                    .unwrap_or_else(|| call_start.elapsed());
+                let _ = tx_latency.try_send(latency);
+                let _ = tx_debug.try_send(policy.take_debug_shapes());
 
                 match action_and_position {
                     Ok((action, position)) => {
-                        if let Err(TrySendError::Disconnected(_)) = tx_action.try_send(action) {
+                        let action = if budget.is_some_and(|budget| latency > budget) {
+                            match on_overrun {
+                                BudgetOverrunMode::Skip | BudgetOverrunMode::Disqualify => None,
+                                BudgetOverrunMode::HoldLast => last_on_time.clone(),
+                                BudgetOverrunMode::ZeroVelocity => Some(Action::Move {
+                                    id: agent_id,
+                                    velocity: (0.0, 0.0),
+                                }),
+                            }
+                        } else {
+                            last_on_time = Some(action.clone());
+                            Some(action)
+                        };
+
+                        if let Some(action) = action
+                            && let Err(TrySendError::Disconnected(_)) = tx_action.try_send(action)
+                        {
                             break; // main thread has exited
                         }
-                        if let Err(TrySendError::Disconnected(_)) = tx_position.try_send(position) {
+                        if let Some(position) = position
+                            && let Err(TrySendError::Disconnected(_)) =
+                                tx_position.try_send((position, true_position))
+                        {
                             break;
                         }
+                        let _ = tx_uncertainty.try_send(policy.take_pose_uncertainty());
                     }
                     Err(e) => {
-                        eprintln!("Error calling policy: {:#?}", e);
-                        break; // exit thread on error
+                        error!("Error calling policy: {}", format_py_err(&e));
+                        match on_error {
+                            PolicyErrorMode::Stop => {
+                                let _ = tx_error.send(e);
+                                break; // exit thread on error
+                            }
+                            PolicyErrorMode::Restart => started_up = false,
+                            PolicyErrorMode::NoOp => {
+                                let stop = Action::Move {
+                                    id: agent_id,
+                                    velocity: (0.0, 0.0),
+                                };
+                                if let Err(TrySendError::Disconnected(_)) = tx_action.try_send(stop)
+                                {
+                                    break;
+                                }
+                            }
+                        }
                     }
                 }
                 start = Instant::now();
@@ -131,15 +324,231 @@ impl PolicyBridge {
             tx_state,
             rx_action,
             rx_position,
+            rx_debug,
+            rx_uncertainty,
+            rx_error,
+            rx_latency,
+            budget,
+            tx_episode_end,
+            worker: Some(worker),
+            rx_worker_done,
         })
     }
 }
 
+/// The decision maker behind a `PolicyBridge`. Only ever called from the bridge's worker thread.
+pub trait Policy: Send + 'static {
+    /// Called once with the first state, before the first `get_action`.
+    fn startup(
+        &mut self,
+        state: &GameState,
+        metadata: &MapMetadata,
+        config: &MazeConfig,
+    ) -> PyResult<()>;
+
+    /// Returns the next action and, if the policy reports one, its estimate of the agent position.
+    fn get_action(
+        &mut self,
+        state: GameState,
+        grid: Arc<RwLock<Py<OccupancyGrid>>>,
+        elapsed: f32,
+    ) -> PyResult<(Action, Option<(f32, f32)>)>;
+
+    /// Called once when the simulation stops.
+    fn on_episode_end(&mut self, summary: EvaluationSummary) -> PyResult<()>;
+
+    /// Hands over the shapes drawn during the last `get_action` call, see `DebugDraw`.
+    fn take_debug_shapes(&mut self) -> Vec<DebugShape> {
+        Vec::new()
+    }
+
+    /// Hands over how unsure the policy was of the position from the last `get_action` call.
+    fn take_pose_uncertainty(&mut self) -> PoseUncertainty {
+        PoseUncertainty::default()
+    }
+
+    /// Hands over how long the policy itself took in the last `get_action` call, for policies
+    /// that spend part of the call waiting on something else. None counts the whole call.
+    fn take_latency(&mut self) -> Option<Duration> {
+        None
+    }
+}
+
+/// A Python object implementing `AgentProtocol`, called in this interpreter.
+pub struct PythonPolicy {
+    policy: Py<PyAny>,
+    debug_draw: Py<DebugDraw>,
+    uncertainty: PoseUncertainty,
+    /// How long the last `get_action` method call took, not counting the wait for the GIL.
+    latency: Option<Duration>,
+    /// Whether an unreadable pose uncertainty was already reported, so it is only warned once.
+    warned_uncertainty: bool,
+}
+
+impl PythonPolicy {
+    /// Wraps `policy` and gives it a `debug_draw` attribute whose heatmaps follow `grid`.
+    pub fn new(policy: &Py<PyAny>, grid: &PlayerGrid) -> PyResult<Self> {
+        Python::attach(|py| {
+            let grid = grid.0.read().unwrap().clone_ref(py);
+            let debug_draw = Py::new(py, DebugDraw::new(&grid.borrow(py)))?;
+            // A policy's own `debug_draw` is left alone. Policies that cannot take new
+            // attributes, such as ones with `__slots__`, simply do not get to draw.
+            if policy.bind(py).hasattr("debug_draw")? {
+                warn!("Policy already has a debug_draw attribute; leaving it in place");
+            } else if let Err(e) = policy.setattr(py, "debug_draw", debug_draw.clone_ref(py)) {
+                warn!("Policy has no debug_draw attribute: {}", e);
+            }
+            Ok(Self {
+                policy: policy.clone_ref(py),
+                debug_draw,
+                uncertainty: PoseUncertainty::default(),
+                latency: None,
+                warned_uncertainty: false,
+            })
+        })
+    }
+}
+
+impl Policy for PythonPolicy {
+    fn startup(
+        &mut self,
+        state: &GameState,
+        metadata: &MapMetadata,
+        config: &MazeConfig,
+    ) -> PyResult<()> {
+        Python::attach(|py| {
+            let state = Py::new(py, state.clone())?;
+            call_hook(
+                py,
+                &self.policy,
+                "startup",
+                (state, metadata.clone(), config.clone()),
+            )
+        })
+    }
+
+    fn get_action(
+        &mut self,
+        state: GameState,
+        grid: Arc<RwLock<Py<OccupancyGrid>>>,
+        elapsed: f32,
+    ) -> PyResult<(Action, Option<(f32, f32)>)> {
+        Python::attach(|py| {
+            let state = Py::new(py, state)?;
+            let grid = Py::new(py, OccupancyGridView { inner: grid })?;
+            // Start from a blank drawing, dropping anything drawn outside `get_action`.
+            self.debug_draw.borrow_mut(py).take();
+            let call_start = Instant::now();
+            let action = self
+                .policy
+                .call_method(py, "get_action", (state, grid, elapsed), None);
+            self.latency = Some(call_start.elapsed());
+            let action: Action = action?.extract(py)?;
+
+            let position: (f32, f32) = self.policy.getattr(py, "position")?.extract(py)?;
+            // The uncertainty is only drawn and scored, so a malformed one is not fatal.
+            self.uncertainty = match read_pose_uncertainty(self.policy.bind(py)) {
+                Ok(uncertainty) => uncertainty,
+                Err(e) => {
+                    if !self.warned_uncertainty {
+                        warn!("Ignoring the policy's pose uncertainty: {}", e);
+                        self.warned_uncertainty = true;
+                    }
+                    PoseUncertainty::default()
+                }
+            };
+            Ok((action, Some(position)))
+        })
+    }
+
+    fn on_episode_end(&mut self, summary: EvaluationSummary) -> PyResult<()> {
+        Python::attach(|py| call_hook(py, &self.policy, "on_episode_end", (summary,)))
+    }
+
+    fn take_debug_shapes(&mut self) -> Vec<DebugShape> {
+        Python::attach(|py| self.debug_draw.borrow_mut(py).take())
+    }
+
+    fn take_pose_uncertainty(&mut self) -> PoseUncertainty {
+        std::mem::take(&mut self.uncertainty)
+    }
+
+    fn take_latency(&mut self) -> Option<Duration> {
+        self.latency.take()
+    }
+}
+
+/// Reads the policy's optional `particles`, rows of (x, y, weight), and its 2x2 `covariance`,
+/// also accepted as `pose_particles` and `pose_covariance`. Both may be nested sequences or NumPy
+/// arrays.
+fn read_pose_uncertainty(policy: &Bound<'_, PyAny>) -> PyResult<PoseUncertainty> {
+    let particles: Option<Vec<[f32; 3]>> =
+        optional_array_attr(policy, &["particles", "pose_particles"])?;
+    let covariance: Option<[[f32; 2]; 2]> =
+        optional_array_attr(policy, &["covariance", "pose_covariance"])?;
+    Ok(PoseUncertainty {
+        particles: (particles.into_iter().flatten())
+            .map(|[x, y, weight]| (Vec2::new(x, y), weight))
+            .collect(),
+        covariance: covariance.map(|[x_axis, y_axis]| Mat2::from_cols_array_2d(&[x_axis, y_axis])),
+    })
+}
+
+/// Extracts the first of `names` that is set to something other than None, going through
+/// `tolist()` for NumPy arrays.
+fn optional_array_attr<T>(policy: &Bound<'_, PyAny>, names: &[&str]) -> PyResult<Option<T>>
+where
+    T: for<'py> FromPyObject<'py>,
+{
+    for name in names {
+        if !policy.hasattr(name)? {
+            continue;
+        }
+        let mut value = policy.getattr(name)?;
+        if value.is_none() {
+            continue;
+        }
+        if value.hasattr("tolist")? {
+            value = value.call_method0("tolist")?;
+        }
+        return value.extract().map(Some);
+    }
+    Ok(None)
+}
+
+/// Calls one of the policy's optional lifecycle hooks, if it defines it.
+fn call_hook<'py>(
+    py: Python<'py>,
+    policy: &Py<PyAny>,
+    name: &str,
+    args: impl PyCallArgs<'py>,
+) -> PyResult<()> {
+    if policy.bind(py).hasattr(name)? {
+        policy.call_method1(py, name, args)?;
+    }
+    Ok(())
+}
+
+/// Formats an exception the way Python would print it, traceback first.
+fn format_py_err(err: &PyErr) -> String {
+    Python::attach(|py| {
+        let traceback = err
+            .traceback(py)
+            .and_then(|tb| tb.format().ok())
+            .unwrap_or_default();
+        format!("{}{}", traceback, err)
+    })
+}
+
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn send_game_states(
     time: Res<Time>,
     mut t: ResMut<PolicyTimer>,
     scores: Res<FlagCaptureCounts>,
+    mut rewards: ResMut<RewardTracker>,
+    mut last_action: ResMut<LastActionResult>,
+    mut observed: ResMut<ObservedObjects>,
+    mut stats: ResMut<PolicyStats>,
     config: Res<MazeConfig>,
     player_grid: Res<PlayerGrid>,
     true_grid: Res<TrueGrid>,
@@ -155,8 +564,9 @@ fn send_game_states(
         ),
         With<Agent>,
     >,
+    mut collisions: Query<&mut CollisionReport, With<Agent>>,
     kinds: Query<(Option<&Wall>, Option<&Flag>, Option<&CapturePoint>)>,
-    flags: Query<&Flag>,
+    objects: MapObjects,
 ) {
     if !t.0.tick(time.delta()).just_finished() {
         return;
@@ -166,39 +576,137 @@ fn send_game_states(
         return;
     };
 
-    let (noisy_agent_state, true_agent_state) =
-        collect_agent_state(&config, &spatial_query, agent, &kinds);
+    // Only this system sends states, so a free slot stays free until the send below. Checking
+    // first keeps the reward, impacts and action result for the next state the policy gets.
+    if bridge.agent_bridge.tx_state.is_full() {
+        stats.missed_ticks += 1;
+        return;
+    }
 
-    let noisy_state = GameState {
-        agent: noisy_agent_state,
-        total_flags: flags.iter().count() as u32,
-        collected_flags: scores.0,
-        world_width: 100.0,
-        world_height: 100.0,
+    let Ok(mut collisions) = collisions.single_mut() else {
+        return;
     };
 
-    let true_state = GameState {
+    let (noisy_agent_state, true_agent_state, seen) =
+        collect_agent_state(&config, &spatial_query, agent, &kinds, &collisions);
+    collisions.impacts = 0;
+    let carried = objects
+        .flags
+        .iter()
+        .find(|(entity, _, _)| Some(entity.index()) == true_agent_state.flag)
+        .map(|(entity, _, _)| entity);
+    objects.observe(
+        &mut observed,
+        &seen,
+        carried,
+        noisy_agent_state.position.into(),
+    );
+
+    let mapping_accuracy = Python::attach(|py| {
+        let player_grid = player_grid.0.read().unwrap();
+        let true_grid = true_grid.0.read().unwrap();
+        let (error, total) = player_grid.borrow(py).mapping_errors(&true_grid.borrow(py));
+        1.0 - error as f32 / total.max(1) as f32
+    });
+    let (reward, cumulative_reward) = rewards.current_step(mapping_accuracy, &config.reward);
+    let action_result = last_action.0.remove(&noisy_agent_state.id);
+    let metadata = MapMetadata::from_config(&config);
+    let total_flags = objects.flags.iter().count() as u32;
+    let image = config.image_observation.enabled.then(|| {
+        let objects = match config.image_observation.grid {
+            GridOverlay::True => FrameObjects {
+                flags: objects
+                    .flag_infos()
+                    .iter()
+                    .map(|f| f.position.into())
+                    .collect(),
+                capture_points: (objects.capture_point_infos().iter())
+                    .map(|c| c.position.into())
+                    .collect(),
+                agent: Some(true_agent_state.position.into()),
+                ghost_agent: None,
+            },
+            GridOverlay::Player | GridOverlay::None => FrameObjects {
+                flags: observed.flags().iter().map(|f| f.position.into()).collect(),
+                capture_points: (observed.capture_points().iter())
+                    .map(|c| c.position.into())
+                    .collect(),
+                agent: Some(noisy_agent_state.position.into()),
+                ghost_agent: None,
+            },
+        };
+        let grid = match config.image_observation.grid {
+            GridOverlay::None => None,
+            GridOverlay::Player => Some(&player_grid.0),
+            GridOverlay::True => Some(&true_grid.0),
+        };
+        let world_size = Vec2::new(metadata.world_width, metadata.world_height);
+        Python::attach(|py| {
+            let grid = grid.map(|grid| grid.read().unwrap().clone_ref(py));
+            let grid = grid.as_ref().map(|grid| grid.borrow(py));
+            ImageObservation::render(
+                &config.image_observation,
+                world_size,
+                grid.as_deref(),
+                &objects,
+            )
+        })
+    });
+    let privileged_info = config.privileged_info.then(|| PrivilegedInfo {
+        agent: true_agent_state.clone(),
+        flags: objects.flag_infos(),
+        capture_points: objects.capture_point_infos(),
+    });
+
+    let true_position: Vec2 = true_agent_state.position.into();
+    let true_state = bridge.test_bridge.is_some().then(|| GameState {
         agent: true_agent_state,
-        total_flags: flags.iter().count() as u32,
+        total_flags,
         collected_flags: scores.0,
-        world_width: 100.0,
-        world_height: 100.0,
+        remaining_flags: total_flags.saturating_sub(scores.0),
+        flags: observed.flags(),
+        capture_points: observed.capture_points(),
+        world_width: metadata.world_width,
+        world_height: metadata.world_height,
+        metadata: metadata.clone(),
+        reward,
+        cumulative_reward,
+        action_result: action_result.clone(),
+        privileged_info: privileged_info.clone(),
+        image: image.clone(),
+    });
+
+    let noisy_state = GameState {
+        agent: noisy_agent_state,
+        total_flags,
+        collected_flags: scores.0,
+        remaining_flags: total_flags.saturating_sub(scores.0),
+        flags: observed.flags(),
+        capture_points: observed.capture_points(),
+        world_width: metadata.world_width,
+        world_height: metadata.world_height,
+        metadata,
+        reward,
+        cumulative_reward,
+        action_result,
+        privileged_info,
+        image,
     };
 
     match bridge
         .agent_bridge
         .tx_state
-        .try_send((noisy_state, player_grid.0.clone()))
+        .try_send((noisy_state, player_grid.0.clone(), true_position))
     {
-        Ok(_) => {}
-        Err(TrySendError::Full(_)) => { /* worker still busy; skip this one */ }
+        Ok(_) => rewards.finish_step(mapping_accuracy, reward),
+        Err(TrySendError::Full(_)) => stats.missed_ticks += 1,
         Err(TrySendError::Disconnected(_)) => {
             /* Agent worker has died */
             return;
         }
     }
 
-    if let Some(test) = &bridge.test_bridge {
+    if let (Some(test), Some(true_state)) = (&bridge.test_bridge, true_state) {
         match test
             .tx_state
             .try_send((true_state, true_grid.0.clone(), player_grid.0.clone()))
@@ -216,6 +724,7 @@ fn apply_actions(
     mut movement_event_writer: MessageWriter<MovementMessage>,
     mut pickup_event_writer: MessageWriter<FlagPickupMessage>,
     mut drop_event_writer: MessageWriter<FlagDropMessage>,
+    mut result_writer: MessageWriter<ActionResultMessage>,
 ) {
     let Some(bridge) = bridge else {
         return;
@@ -229,40 +738,50 @@ fn apply_actions(
         return;
     };
 
+    let id = match action {
+        Action::Move { id, .. } | Action::PickupFlag { id } | Action::DropFlag { id } => id,
+    };
+    if !check_agent_exists(id, agents) {
+        warn!("Rejected action: agent with id {} does not exist", id);
+        result_writer.write(ActionResultMessage {
+            agent_id: id,
+            result: ActionResult::rejected(action, format!("agent with id {} does not exist", id)),
+        });
+        return;
+    }
+
     match action {
         Action::Move { id, velocity } => {
-            if !check_agent_exists(id, agents) {
-                return;
-            }
             movement_event_writer.write(MovementMessage::TranslateById(id, velocity.into()));
         }
         Action::PickupFlag { id } => {
-            if !check_agent_exists(id, agents) {
-                return;
-            }
             pickup_event_writer.write(FlagPickupMessage { agent_id: id });
         }
         Action::DropFlag { id } => {
-            if !check_agent_exists(id, agents) {
-                return;
-            }
             drop_event_writer.write(FlagDropMessage { agent_id: id });
         }
     }
 }
 
+fn record_action_results(
+    mut reader: MessageReader<ActionResultMessage>,
+    mut last: ResMut<LastActionResult>,
+) {
+    for message in reader.read() {
+        last.0.insert(message.agent_id, message.result.clone());
+    }
+}
+
 fn update_estimated_position_text(
     bridge: Option<Res<Bridge>>,
-    agent_transform: Query<&Transform, (With<Agent>, Without<GhostAgent>)>,
+    mut uncertainty: ResMut<PoseUncertainty>,
+    mut consistency: ResMut<EstimateConsistency>,
     mut ghost_agent_transform: Query<&mut Transform, (With<GhostAgent>, Without<Agent>)>,
     mut query: Query<&mut Text, With<EstimatedPositionText>>,
 ) {
     let Some(bridge) = bridge else {
         return;
     };
-    let Some(agent_transform) = agent_transform.single().ok() else {
-        return;
-    };
     let Some(mut ghost_transform) = ghost_agent_transform.single_mut().ok() else {
         return;
     };
@@ -271,22 +790,93 @@ fn update_estimated_position_text(
         return;
     };
 
-    let mut latest: Option<(f32, f32)> = None;
-    while let Ok(position) = bridge.agent_bridge.rx_position.try_recv() {
-        latest = Some(position);
-    }
-    let Some((x, y)) = latest else {
+    let Some(((x, y), true_position)) = bridge.agent_bridge.rx_position.try_iter().last() else {
         return;
     };
+    if let Some(latest) = bridge.agent_bridge.rx_uncertainty.try_iter().last() {
+        *uncertainty = latest;
+    }
 
-    let error = ((agent_transform.translation.x - x).powi(2)
-        + (agent_transform.translation.z - y).powi(2))
-    .sqrt();
+    // Scored against where the agent was when the policy got the state, not where it is now.
+    let offset = Vec2::new(x, y) - true_position;
+    let error = offset.length();
 
     text.0 = format!("Estimated Agent Position: ({x:.2}, {y:.2}) [{error:.2}]");
+    if let Some(covariance) = uncertainty.covariance()
+        && let Some(nees) = consistency.record(offset, covariance)
+    {
+        text.0 += &format!(
+            " NEES {nees:.2} (mean {:.2}, {:.0}% within 95% bounds)",
+            consistency.mean(),
+            consistency.within_fraction() * 100.0
+        );
+    }
     ghost_transform.translation = Vec3::new(x, 0.0, y);
 }
 
+fn update_debug_shapes(bridge: Option<Res<Bridge>>, mut shapes: ResMut<DebugShapes>) {
+    let Some(bridge) = bridge else {
+        return;
+    };
+    let Some(latest) = bridge.agent_bridge.rx_debug.try_iter().last() else {
+        return;
+    };
+    // Only touch the resource when the drawing changes, so the window keeps its entities.
+    if shapes.shapes != latest {
+        shapes.shapes = latest;
+    }
+}
+
+fn stop_on_policy_error(
+    bridge: Option<Res<Bridge>>,
+    slot: Res<PolicyError>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let Some(bridge) = bridge else {
+        return;
+    };
+    if let Ok(err) = bridge.agent_bridge.rx_error.try_recv() {
+        *slot.0.lock().unwrap() = Some(err);
+        exit.write(AppExit::error());
+    }
+}
+
+fn record_policy_latency(
+    bridge: Option<Res<Bridge>>,
+    config: Res<MazeConfig>,
+    mut stats: ResMut<PolicyStats>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let Some(bridge) = bridge else {
+        return;
+    };
+    let bridge = &bridge.agent_bridge;
+
+    while let Ok(latency) = bridge.rx_latency.try_recv() {
+        stats.latencies.push(latency);
+
+        let Some(budget) = bridge.budget else {
+            continue;
+        };
+        if latency <= budget {
+            continue;
+        }
+
+        stats.overruns += 1;
+        warn!(
+            "Policy took {:.2}ms, over its {:.2}ms budget",
+            latency.as_secs_f32() * 1000.0,
+            budget.as_secs_f32() * 1000.0
+        );
+
+        if config.agent.on_budget_overrun == BudgetOverrunMode::Disqualify && !stats.disqualified {
+            error!("Policy disqualified for going over its time budget");
+            stats.disqualified = true;
+            exit.write(AppExit::error());
+        }
+    }
+}
+
 fn on_test_harness_stop(bridge: Option<Res<Bridge>>, mut exit: MessageWriter<AppExit>) {
     let Some(bridge) = bridge else {
         return;
@@ -294,21 +884,72 @@ fn on_test_harness_stop(bridge: Option<Res<Bridge>>, mut exit: MessageWriter<App
     if let Some(test) = &bridge.test_bridge
         && test.rx_stop.try_recv().is_ok()
     {
-        println!("Test harness requested stop; exiting");
+        info!("Test harness requested stop; exiting");
 
         exit.write(AppExit::Success);
     }
 }
 
-fn shutdown_workers_on_exit(
+#[allow(clippy::too_many_arguments)]
+fn report_summary_on_exit(
     mut exit_ev: MessageReader<AppExit>,
-    mut bridge: Option<ResMut<Bridge>>,
+    time: Res<Time>,
+    scores: Res<FlagCaptureCounts>,
+    rewards: Res<RewardTracker>,
+    stats: Res<PolicyStats>,
+    exploration: Res<ExplorationTracker>,
+    player_grid: Res<PlayerGrid>,
+    true_grid: Res<TrueGrid>,
+    bridge: Option<Res<Bridge>>,
+    agent: Query<&CollisionReport, With<Agent>>,
+    flags: Query<&Flag>,
 ) {
     if exit_ev.read().next().is_none() {
         return;
     }
 
-    bridge.take();
+    let summary = collect_summary(
+        &time,
+        &scores,
+        &rewards,
+        &stats,
+        &exploration,
+        &player_grid,
+        &true_grid,
+        &agent,
+        &flags,
+    );
+    info!("{}", summary);
+
+    let Some(bridge) = bridge else {
+        return;
+    };
+    let _ = bridge.agent_bridge.tx_episode_end.try_send(summary.clone());
+    if let Some(test) = &bridge.test_bridge {
+        let _ = test.tx_summary.try_send(summary);
+    }
+}
+
+fn shutdown_workers_on_exit(mut exit_ev: MessageReader<AppExit>, bridge: Option<ResMut<Bridge>>) {
+    if exit_ev.read().next().is_none() {
+        return;
+    }
+
+    // Let the policy finish its current call and run `on_episode_end` before the app goes away,
+    // but do not let a policy that never returns keep the app from exiting.
+    if let Some(mut bridge) = bridge
+        && let Some(worker) = bridge.agent_bridge.worker.take()
+    {
+        match (bridge.agent_bridge.rx_worker_done).recv_timeout(WORKER_SHUTDOWN_TIMEOUT) {
+            Err(RecvTimeoutError::Timeout) => error!(
+                "Policy did not stop within {:.0}s of the end of the run; leaving it running",
+                WORKER_SHUTDOWN_TIMEOUT.as_secs_f32()
+            ),
+            _ => {
+                let _ = worker.join();
+            }
+        }
+    }
 }
 
 fn check_agent_exists(id: u32, agents: Query<(Entity, &Agent)>) -> bool {
@@ -0,0 +1,361 @@
+//! A policy running in another process, reached over a local socket.
+//!
+//! Set `AgentConfig.policy_server` to `unix:/path/to/socket` or to a TCP address such as
+//! `127.0.0.1:5555`. The simulator connects as a client; the policy process listens.
+//!
+//! Every message is a single JSON object on its own line, with a `type` field.
+//! Simulator to policy:
+//!
+//! - `{"type": "startup", "state": GameState, "metadata": MapMetadata, "config": MazeConfig}`
+//!   is sent once per connection, before the first step. No reply is expected.
+//! - `{"type": "step", "state": GameState, "elapsed": float, "grid": [Cell]}` asks for the next
+//!   action. `grid` holds the player grid cells that changed since the previous step; after a
+//!   (re)connect it holds every cell.
+//! - `{"type": "episode_end", "summary": EvaluationSummary}` is sent when the simulation stops.
+//!   No reply is expected.
+//!
+//! Each step must be answered with one of:
+//!
+//! - `{"type": "action", "action": Action, "position": [x, y] | null, "grid": [Cell]}`, where
+//!   `grid` is an optional list of player grid cells the policy wants to overwrite, ignored with
+//!   `use_true_map`, and `position` is its optional estimate of the agent position.
+//! - `{"type": "error", "message": str}`, which is handled according to
+//!   `AgentConfig.on_policy_error`.
+//!
+//! An `Action` is `{"type": "move", "id": int, "velocity": [x, y]}`,
+//! `{"type": "pickup_flag", "id": int}` or `{"type": "drop_flag", "id": int}`.
+//! A `Cell` is `{"x": int, "y": int, "assignment": EntityType | null, "logits": [free, wall,
+//! flag, capture_point]}`, with entity types in snake_case (`"wall"`, `"capture_point"`, ...).
+//!
+//! A reply that takes longer than `AgentConfig.policy_budget_ms` is still used, and handled like
+//! any other overrun according to `AgentConfig.on_budget_overrun`. Connecting, sending and
+//! waiting for a reply only time out after five seconds. If the connection breaks or times out,
+//! the call fails with a `ConnectionError` and the connection is dropped. With
+//! `on_policy_error: restart` the simulator reconnects on the next step and resends `startup`,
+//! so a policy process can crash and be restarted without taking the simulator down.
+
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use pyo3::exceptions::{PyConnectionError, PyRuntimeError};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::Action;
+use crate::core::MazeConfig;
+use crate::occupancy_grid::{LOGIT_CLAMP, OccupancyGrid, OccupancyGridEntry};
+use crate::python::game_state::{EntityType, GameState, MapMetadata};
+use crate::python::policy::Policy;
+use crate::python::summary::EvaluationSummary;
+
+/// How long to wait on the policy server before giving up on the connection. The policy budget is
+/// enforced by the bridge, so a late reply is not an I/O error.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request<'a> {
+    Startup {
+        state: &'a GameState,
+        metadata: &'a MapMetadata,
+        config: &'a MazeConfig,
+    },
+    Step {
+        state: &'a GameState,
+        elapsed: f32,
+        grid: Vec<Cell>,
+    },
+    EpisodeEnd {
+        summary: &'a EvaluationSummary,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Response {
+    Action {
+        action: Action,
+        #[serde(default)]
+        position: Option<(f32, f32)>,
+        #[serde(default)]
+        grid: Vec<Cell>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Cell {
+    x: usize,
+    y: usize,
+    assignment: Option<EntityType>,
+    logits: [f32; 4],
+}
+
+impl Cell {
+    fn new(x: usize, y: usize, entry: &OccupancyGridEntry) -> Self {
+        Self {
+            x,
+            y,
+            assignment: entry.assignment,
+            logits: [
+                entry.logit_free,
+                entry.logit_wall,
+                entry.logit_flag,
+                entry.logit_capture_point,
+            ],
+        }
+    }
+}
+
+struct Connection {
+    reader: BufReader<Box<dyn Read + Send>>,
+    writer: Box<dyn Write + Send>,
+}
+
+impl Connection {
+    /// Connects to `address`, giving up on connecting, and later on each read and write, after
+    /// `timeout`.
+    fn open(address: &str, timeout: Duration) -> std::io::Result<Self> {
+        let (reader, writer): (Box<dyn Read + Send>, Box<dyn Write + Send>) =
+            match address.strip_prefix("unix:") {
+                #[cfg(unix)]
+                Some(path) => {
+                    let stream = UnixStream::connect(path)?;
+                    stream.set_read_timeout(Some(timeout))?;
+                    stream.set_write_timeout(Some(timeout))?;
+                    (Box::new(stream.try_clone()?), Box::new(stream))
+                }
+                #[cfg(not(unix))]
+                Some(_) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Unsupported,
+                        "Unix sockets are not supported on this platform",
+                    ));
+                }
+                None => {
+                    let stream = connect_tcp(address, timeout)?;
+                    stream.set_nodelay(true)?;
+                    stream.set_read_timeout(Some(timeout))?;
+                    stream.set_write_timeout(Some(timeout))?;
+                    (Box::new(stream.try_clone()?), Box::new(stream))
+                }
+            };
+
+        Ok(Self {
+            reader: BufReader::new(reader),
+            writer,
+        })
+    }
+
+    fn send(&mut self, request: &Request) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(request)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        self.writer.flush()
+    }
+
+    fn receive(&mut self) -> std::io::Result<Response> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+/// Like `TcpStream::connect`, but trying each resolved address for at most `timeout`.
+fn connect_tcp(address: &str, timeout: Duration) -> std::io::Result<TcpStream> {
+    let mut last_error = None;
+    for address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        std::io::Error::new(ErrorKind::InvalidInput, "address resolved to nothing")
+    }))
+}
+
+/// A policy in another process, speaking the protocol described at the top of this module.
+pub struct RemotePolicy {
+    address: String,
+    connection: Option<Connection>,
+    /// The startup message, kept around to resend after reconnecting.
+    startup: Option<Vec<u8>>,
+    /// The player grid as the policy last saw it, used to send only the cells that changed.
+    sent_grid: Vec<OccupancyGridEntry>,
+    /// False when the player grid is the true grid, which must not be written to.
+    mapping: bool,
+}
+
+impl RemotePolicy {
+    /// A policy at `address`, connected to on the first call.
+    pub fn new(address: String) -> Self {
+        Self {
+            address,
+            connection: None,
+            startup: None,
+            sent_grid: Vec::new(),
+            mapping: false,
+        }
+    }
+
+    fn connection(&mut self) -> std::io::Result<&mut Connection> {
+        if self.connection.is_none() {
+            let mut connection = Connection::open(&self.address, IO_TIMEOUT)?;
+            if let Some(startup) = &self.startup {
+                connection.writer.write_all(startup)?;
+                connection.writer.flush()?;
+            }
+            self.sent_grid.clear();
+            self.connection = Some(connection);
+        }
+        Ok(self.connection.as_mut().unwrap())
+    }
+
+    /// Runs `f` on the connection, dropping it on failure so the next call reconnects.
+    fn with_connection<T>(
+        &mut self,
+        f: impl FnOnce(&mut Connection) -> std::io::Result<T>,
+    ) -> PyResult<T> {
+        let result = self.connection().and_then(f);
+        result.map_err(|e| {
+            // A late reply would answer the wrong request, so a timeout drops the connection too.
+            self.connection = None;
+            let reason = match e.kind() {
+                ErrorKind::WouldBlock | ErrorKind::TimedOut => {
+                    format!("timed out after {:.0}ms", IO_TIMEOUT.as_secs_f32() * 1000.0)
+                }
+                _ => e.to_string(),
+            };
+            PyConnectionError::new_err(format!("Policy server {}: {}", self.address, reason))
+        })
+    }
+
+    /// Collects the cells of `grid` that changed since they were last sent, and remembers them.
+    fn grid_delta(&mut self, grid: &OccupancyGrid) -> Vec<Cell> {
+        if self.sent_grid.len() != grid.grid.len() {
+            self.sent_grid = vec![OccupancyGridEntry::default(); grid.grid.len()];
+            return grid
+                .grid
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| {
+                    self.sent_grid[index] = *entry;
+                    Cell::new(index % grid.width, index / grid.width, entry)
+                })
+                .collect();
+        }
+
+        let mut delta = Vec::new();
+        for (index, (entry, sent)) in grid.grid.iter().zip(self.sent_grid.iter_mut()).enumerate() {
+            if entry != sent {
+                *sent = *entry;
+                delta.push(Cell::new(index % grid.width, index / grid.width, entry));
+            }
+        }
+        delta
+    }
+
+    /// Writes the policy's cell updates into `grid`, without echoing them back on the next step.
+    fn apply_cells(&mut self, grid: &mut OccupancyGrid, cells: Vec<Cell>) {
+        for cell in cells {
+            if cell.x >= grid.width || cell.y >= grid.height {
+                continue;
+            }
+            let index = cell.x + cell.y * grid.width;
+            let [free, wall, flag, capture_point] = cell
+                .logits
+                .map(|logit| logit.clamp(-LOGIT_CLAMP, LOGIT_CLAMP));
+            let entry = OccupancyGridEntry {
+                assignment: cell.assignment,
+                logit_free: free,
+                logit_wall: wall,
+                logit_flag: flag,
+                logit_capture_point: capture_point,
+            };
+            grid.grid[index] = entry;
+            if let Some(sent) = self.sent_grid.get_mut(index) {
+                *sent = entry;
+            }
+        }
+    }
+}
+
+impl Policy for RemotePolicy {
+    fn startup(
+        &mut self,
+        state: &GameState,
+        metadata: &MapMetadata,
+        config: &MazeConfig,
+    ) -> PyResult<()> {
+        let mut line = serde_json::to_vec(&Request::Startup {
+            state,
+            metadata,
+            config,
+        })
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to serialize startup: {}", e)))?;
+        line.push(b'\n');
+
+        self.mapping = !config.use_true_map;
+        // Connecting sends the startup message.
+        self.startup = Some(line);
+        self.connection = None;
+        self.with_connection(|_| Ok(()))
+    }
+
+    fn get_action(
+        &mut self,
+        state: GameState,
+        grid: Arc<RwLock<Py<OccupancyGrid>>>,
+        elapsed: f32,
+    ) -> PyResult<(Action, Option<(f32, f32)>)> {
+        // Connect first, since reconnecting resets which cells the policy has seen.
+        self.with_connection(|_| Ok(()))?;
+
+        let delta = Python::attach(|py| {
+            let grid = grid.read().unwrap();
+            self.grid_delta(&grid.borrow(py))
+        });
+
+        let request = Request::Step {
+            state: &state,
+            elapsed,
+            grid: delta,
+        };
+        let response = self.with_connection(|connection| {
+            connection.send(&request)?;
+            connection.receive()
+        })?;
+
+        match response {
+            Response::Action {
+                action,
+                position,
+                grid: cells,
+            } => {
+                if self.mapping && !cells.is_empty() {
+                    Python::attach(|py| {
+                        let grid = grid.read().unwrap();
+                        self.apply_cells(&mut grid.borrow_mut(py), cells);
+                    });
+                }
+                Ok((action, position))
+            }
+            Response::Error { message } => Err(PyRuntimeError::new_err(message)),
+        }
+    }
+
+    fn on_episode_end(&mut self, summary: EvaluationSummary) -> PyResult<()> {
+        self.with_connection(|connection| {
+            connection.send(&Request::EpisodeEnd { summary: &summary })
+        })
+    }
+}
@@ -8,7 +8,7 @@ use std::{
 
 use crate::{
     occupancy_grid::{OccupancyGrid, OccupancyGridView},
-    python::game_state::GameState,
+    python::{game_state::GameState, policy::PolicyErrorSlot, summary::EvaluationSummary},
 };
 
 #[gen_stub_pyclass]
@@ -21,6 +21,9 @@ pub struct StateQueue {
         Arc<RwLock<Py<OccupancyGrid>>>,
     )>,
     pub tx_stop: crossbeam_channel::Sender<()>,
+    pub rx_summary: Receiver<EvaluationSummary>,
+    pub summary: Option<EvaluationSummary>,
+    pub error: PolicyErrorSlot,
     pub join: Option<std::thread::JoinHandle<()>>,
     pub rate_hz: f32,
 }
@@ -34,11 +37,56 @@ impl StateQueue {
     }
 
     /// Wait for next GameState (timeout ms optional). Returns None on timeout.
+    /// Raises the policy's exception if it stopped the simulation.
     #[allow(clippy::type_complexity)]
     fn get<'py>(
         &self,
         py: Python<'py>,
         timeout_ms: Option<u64>,
+    ) -> PyResult<Option<(Py<GameState>, Py<OccupancyGridView>, Py<OccupancyGridView>)>> {
+        self.raise_policy_error()?;
+
+        let next = self.recv(py, timeout_ms)?;
+        if next.is_none() {
+            self.raise_policy_error()?;
+        }
+        Ok(next)
+    }
+
+    /// Ask the sim to stop.
+    fn stop(&self) {
+        let _ = self.tx_stop.send(());
+    }
+
+    /// Join the sim thread.
+    fn join(&mut self, py: Python<'_>) {
+        if let Some(j) = self.join.take() {
+            let _ = py.detach(|| j.join());
+        }
+    }
+
+    /// The evaluation summary of the run. Returns None until the sim has stopped.
+    fn summary(&mut self) -> Option<EvaluationSummary> {
+        if self.summary.is_none() {
+            self.summary = self.rx_summary.try_recv().ok();
+        }
+        self.summary.clone()
+    }
+}
+
+impl StateQueue {
+    fn raise_policy_error(&self) -> PyResult<()> {
+        match self.error.lock().unwrap().take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn recv(
+        &self,
+        py: Python<'_>,
+        timeout_ms: Option<u64>,
     ) -> PyResult<Option<(Py<GameState>, Py<OccupancyGridView>, Py<OccupancyGridView>)>> {
         py.detach(|| match timeout_ms {
             Some(ms) => match self.rx_state.recv_timeout(Duration::from_millis(ms)) {
@@ -72,25 +120,14 @@ impl StateQueue {
             },
         })
     }
-
-    /// Ask the sim to stop.
-    fn stop(&self) {
-        let _ = self.tx_stop.send(());
-    }
-
-    /// Join the sim thread.
-    fn join(&mut self) {
-        if let Some(j) = self.join.take() {
-            let _ = j.join();
-        }
-    }
 }
 
 impl Drop for StateQueue {
     fn drop(&mut self) {
         let _ = self.tx_stop.send(());
         if let Some(j) = self.join.take() {
-            let _ = j.join();
+            // The sim thread needs the GIL to shut down, so release it while waiting.
+            let _ = Python::attach(|py| py.detach(|| j.join()));
         }
     }
 }
@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::gen_stub_pyclass;
+use serde::Serialize;
+
+use crate::agent::{Agent, CollisionReport};
+use crate::flag::{Flag, FlagCaptureCounts};
+use crate::occupancy_grid::{ExplorationTracker, PlayerGrid, TrueGrid};
+use crate::reward::RewardTracker;
+
+/// End-of-run statistics used to grade a policy.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[gen_stub_pyclass]
+#[pyclass(name = "EvaluationSummary", frozen, str)]
+pub struct EvaluationSummary {
+    /// Seconds of simulated time.
+    #[pyo3(get)]
+    pub elapsed: f32,
+
+    #[pyo3(get)]
+    pub total_flags: u32,
+
+    #[pyo3(get)]
+    pub collected_flags: u32,
+
+    /// The sum of all rewards handed to the policy.
+    #[pyo3(get)]
+    pub cumulative_reward: f32,
+
+    /// How many times the agent ran into a wall.
+    #[pyo3(get)]
+    pub total_collisions: u32,
+
+    /// Fraction of wall and free cells that the player grid got wrong.
+    #[pyo3(get)]
+    pub mapping_error: f32,
+
+    /// Fraction of the cells reachable from the spawn point that the player grid has assigned.
+    #[pyo3(get)]
+    pub explored_fraction: f32,
+
+    /// The explored fraction sampled about once per second, as (elapsed seconds, fraction).
+    #[pyo3(get)]
+    pub coverage: Vec<(f32, f32)>,
+
+    /// How long the policy took to compute its actions.
+    #[pyo3(get)]
+    pub policy_latency: LatencyStats,
+
+    /// Whether the run was ended early because the policy went over its time budget.
+    #[pyo3(get)]
+    pub disqualified: bool,
+}
+
+/// Statistics about `get_action` call durations.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[gen_stub_pyclass]
+#[pyclass(name = "LatencyStats", frozen, str)]
+pub struct LatencyStats {
+    /// Number of times the policy was called.
+    #[pyo3(get)]
+    pub calls: u32,
+
+    #[pyo3(get)]
+    pub mean_ms: f32,
+
+    #[pyo3(get)]
+    pub p50_ms: f32,
+
+    #[pyo3(get)]
+    pub p90_ms: f32,
+
+    #[pyo3(get)]
+    pub p99_ms: f32,
+
+    #[pyo3(get)]
+    pub max_ms: f32,
+
+    /// Number of calls that took longer than `policy_budget_ms`.
+    #[pyo3(get)]
+    pub overruns: u32,
+
+    /// Number of policy ticks whose state was dropped because the policy was still busy.
+    #[pyo3(get)]
+    pub missed_ticks: u32,
+}
+
+impl std::fmt::Display for LatencyStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "LatencyStats(calls={}, mean={:.2}ms, p50={:.2}ms, p90={:.2}ms, p99={:.2}ms, max={:.2}ms, overruns={}, missed_ticks={})",
+            self.calls,
+            self.mean_ms,
+            self.p50_ms,
+            self.p90_ms,
+            self.p99_ms,
+            self.max_ms,
+            self.overruns,
+            self.missed_ticks
+        )
+    }
+}
+
+/// Timing of the policy worker, collected over the whole run.
+#[derive(Resource, Debug, Default)]
+pub struct PolicyStats {
+    pub latencies: Vec<Duration>,
+    pub overruns: u32,
+    pub missed_ticks: u32,
+    pub disqualified: bool,
+}
+
+impl PolicyStats {
+    pub fn latency_stats(&self) -> LatencyStats {
+        let mut millis = self
+            .latencies
+            .iter()
+            .map(|latency| latency.as_secs_f32() * 1000.0)
+            .collect::<Vec<_>>();
+        millis.sort_by(f32::total_cmp);
+
+        // Nearest-rank percentile.
+        let percentile = |p: f32| {
+            if millis.is_empty() {
+                return 0.0;
+            }
+            let rank = (p * millis.len() as f32).ceil() as usize;
+            millis[rank.clamp(1, millis.len()) - 1]
+        };
+
+        LatencyStats {
+            calls: millis.len() as u32,
+            mean_ms: millis.iter().sum::<f32>() / millis.len().max(1) as f32,
+            p50_ms: percentile(0.50),
+            p90_ms: percentile(0.90),
+            p99_ms: percentile(0.99),
+            max_ms: millis.last().copied().unwrap_or(0.0),
+            overruns: self.overruns,
+            missed_ticks: self.missed_ticks,
+        }
+    }
+}
+
+impl std::fmt::Display for EvaluationSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "EvaluationSummary(elapsed={:.2}s, flags={}/{}, reward={:.2}, collisions={}, mapping_error={:.1}%, explored={:.1}%, policy_latency={}, disqualified={})",
+            self.elapsed,
+            self.collected_flags,
+            self.total_flags,
+            self.cumulative_reward,
+            self.total_collisions,
+            self.mapping_error * 100.0,
+            self.explored_fraction * 100.0,
+            self.policy_latency,
+            self.disqualified
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn collect_summary(
+    time: &Time,
+    scores: &FlagCaptureCounts,
+    rewards: &RewardTracker,
+    policy: &PolicyStats,
+    exploration: &ExplorationTracker,
+    player_grid: &PlayerGrid,
+    true_grid: &TrueGrid,
+    agent: &Query<&CollisionReport, With<Agent>>,
+    flags: &Query<&Flag>,
+) -> EvaluationSummary {
+    let (mapping_error, explored_fraction) = Python::attach(|py| {
+        let player_grid = player_grid.0.read().unwrap();
+        let true_grid = true_grid.0.read().unwrap();
+        let player_grid = player_grid.borrow(py);
+        let (error, total) = player_grid.mapping_errors(&true_grid.borrow(py));
+        (
+            error as f32 / total.max(1) as f32,
+            exploration.explored_fraction(&player_grid),
+        )
+    });
+
+    EvaluationSummary {
+        elapsed: time.elapsed_secs(),
+        total_flags: flags.iter().count() as u32,
+        collected_flags: scores.0,
+        cumulative_reward: rewards.total,
+        total_collisions: agent.iter().map(|report| report.total_impacts).sum(),
+        mapping_error,
+        explored_fraction,
+        coverage: exploration.coverage.clone(),
+        policy_latency: policy.latency_stats(),
+        disqualified: policy.disqualified,
+    }
+}
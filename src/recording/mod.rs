@@ -0,0 +1,169 @@
+mod raster;
+mod systems;
+
+use bevy::prelude::*;
+use derivative::Derivative;
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_enum};
+use serde::{Deserialize, Serialize};
+
+pub use raster::*;
+
+use crate::core::MazeConfig;
+
+/// Records top-down frames of the run with a CPU rasterizer, so it also works in headless mode.
+#[gen_stub_pyclass]
+#[pyclass(name = "RecordingConfig")]
+#[derive(Debug, Clone, Resource, Reflect, Derivative, Serialize, Deserialize)]
+#[derivative(Default)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct RecordingConfig {
+    /// Directory to write frames into, created if missing. Nothing is recorded when None.
+    #[pyo3(get, set)]
+    pub output_dir: Option<String>,
+
+    #[pyo3(get, set)]
+    pub format: RecordingFormat,
+
+    /// Frames per second of simulated time.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "10.0"))]
+    pub fps: f32,
+
+    /// Image pixels per world unit.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "4.0"))]
+    pub pixels_per_unit: f32,
+
+    /// Which occupancy grid to draw under the walls.
+    #[pyo3(get, set)]
+    pub grid_overlay: GridOverlay,
+}
+
+#[pymethods]
+impl RecordingConfig {
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("RecordingConfig({})", self.__str__()?))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to serialize RecordingConfig: {}",
+                e
+            ))
+        })
+    }
+}
+
+/// A top-down image of an occupancy grid added to every `GameState`, for convolutional policies.
+#[gen_stub_pyclass]
+#[pyclass(name = "ImageObservationConfig")]
+#[derive(Debug, Clone, Reflect, Derivative, Serialize, Deserialize)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct ImageObservationConfig {
+    /// Fill in `GameState.image`.
+    #[pyo3(get, set)]
+    pub enabled: bool,
+
+    /// Image width in pixels.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "64"))]
+    pub width: usize,
+
+    /// Image height in pixels.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "64"))]
+    pub height: usize,
+
+    #[pyo3(get, set)]
+    pub frame: ObservationFrame,
+
+    /// With an egocentric frame, how many world units the image spans across its width.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "40.0"))]
+    pub view_size: f32,
+
+    /// The grid to draw. With the player grid, markers show the estimated agent position and
+    /// the flags and capture points in `GameState.flags` and `GameState.capture_points`. With
+    /// the true grid, they show the true positions of everything.
+    #[pyo3(get, set)]
+    pub grid: GridOverlay,
+}
+
+#[pymethods]
+impl ImageObservationConfig {
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("ImageObservationConfig({})", self.__str__()?))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to serialize ImageObservationConfig: {}",
+                e
+            ))
+        })
+    }
+}
+
+/// What part of the world an image observation shows.
+#[gen_stub_pyclass_enum]
+#[pyclass(name = "ObservationFrame", frozen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObservationFrame {
+    /// The whole maze, scaled to fit the image.
+    Global,
+    /// A window of `view_size` world units centered on the agent. The map does not rotate,
+    /// since the agent has no heading.
+    #[default]
+    Egocentric,
+}
+
+/// How recorded frames are written to `RecordingConfig.output_dir`.
+#[gen_stub_pyclass_enum]
+#[pyclass(name = "RecordingFormat", frozen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingFormat {
+    /// One `frame_00000.png` file per frame.
+    PngSequence,
+    /// A single looping `recording.gif`.
+    #[default]
+    Gif,
+}
+
+/// Which occupancy grid, if any, to draw in a rendered frame.
+#[gen_stub_pyclass_enum]
+#[pyclass(name = "GridOverlay", frozen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GridOverlay {
+    None,
+    /// The grid the policy builds.
+    #[default]
+    Player,
+    /// The ground truth grid.
+    True,
+}
+
+pub struct RecordingPlugin;
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, systems::start_recording.run_if(recording_enabled));
+        app.add_systems(
+            PostUpdate,
+            systems::record_frame
+                .after(TransformSystems::Propagate)
+                .run_if(recording_enabled),
+        );
+        app.add_systems(Last, systems::finish_recording.run_if(recording_enabled));
+    }
+}
+
+fn recording_enabled(config: Res<MazeConfig>) -> bool {
+    config.recording.output_dir.is_some()
+}
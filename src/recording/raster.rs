@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+
+use crate::debug_draw::DebugShape;
+use crate::occupancy_grid::{OccupancyGrid, encode_grid_to_rgba};
+use crate::recording::{ImageObservationConfig, ObservationFrame};
+use crate::scene::WALL_THICKNESS;
+
+const BACKGROUND_COLOR: [u8; 4] = [235, 235, 235, 255];
+const WALL_COLOR: [u8; 4] = [0, 0, 0, 255];
+const FLAG_COLOR: [u8; 4] = [0, 0, 255, 255];
+const CAPTURE_POINT_COLOR: [u8; 4] = [0, 0, 255, 80];
+const AGENT_COLOR: [u8; 4] = [255, 0, 0, 255];
+const GHOST_AGENT_COLOR: [u8; 4] = [255, 0, 0, 128];
+
+const FLAG_RADIUS: f32 = 0.5;
+const CAPTURE_POINT_RADIUS: f32 = 1.5;
+const AGENT_HALF_SIZE: f32 = 0.5;
+/// Recorded debug lines and circles are as thick as this many pixels.
+const DEBUG_LINE_PIXELS: f32 = 1.0;
+/// Text labels cannot be rasterized, so recordings mark where they are anchored.
+const DEBUG_LABEL_PIXELS: f32 = 2.0;
+
+/// The moving parts of a frame, in world (x, y) coordinates.
+#[derive(Debug, Clone, Default)]
+pub struct FrameObjects {
+    pub flags: Vec<Vec2>,
+    pub capture_points: Vec<Vec2>,
+    pub agent: Option<Vec2>,
+    pub ghost_agent: Option<Vec2>,
+}
+
+/// A top-down picture for convolutional policies, see `ImageObservationConfig`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageObservation {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major RGBA pixels.
+    pub pixels: Vec<u8>,
+}
+
+impl ImageObservation {
+    /// Draws `grid`, if any, and the objects, framed as `config` asks. `world_size` is the size of the
+    /// maze, centered on the origin.
+    pub fn render(
+        config: &ImageObservationConfig,
+        world_size: Vec2,
+        grid: Option<&OccupancyGrid>,
+        objects: &FrameObjects,
+    ) -> Self {
+        let image_size = Vec2::new(config.width as f32, config.height as f32);
+        let (center, scale) = match config.frame {
+            ObservationFrame::Global => (Vec2::ZERO, (image_size / world_size).min_element()),
+            ObservationFrame::Egocentric => (
+                objects.agent.unwrap_or_default(),
+                config.width as f32 / config.view_size,
+            ),
+        };
+
+        let mut canvas = Canvas::new(
+            config.width,
+            config.height,
+            center - image_size * 0.5 / scale,
+            scale,
+        );
+        canvas.draw_scene(&[], grid, objects);
+        Self {
+            width: canvas.width,
+            height: canvas.height,
+            pixels: canvas.pixels,
+        }
+    }
+}
+
+/// An RGBA image of part of the world seen from above, drawn on the CPU. World x runs to the
+/// right and world y down, like the rows of an `OccupancyGrid`.
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major RGBA pixels.
+    pub pixels: Vec<u8>,
+    /// World coordinates of the top-left corner of the image.
+    origin: Vec2,
+    /// Pixels per world unit.
+    scale: f32,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize, origin: Vec2, scale: f32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: BACKGROUND_COLOR.repeat(width * height),
+            origin,
+            scale,
+        }
+    }
+
+    /// Draws the grid, walls and objects, in that order.
+    pub fn draw_scene(
+        &mut self,
+        walls: &[(Vec2, Vec2)],
+        grid: Option<&OccupancyGrid>,
+        objects: &FrameObjects,
+    ) {
+        if let Some(grid) = grid {
+            self.draw_grid(grid);
+        }
+        for &(start, end) in walls {
+            self.draw_segment(start, end, WALL_THICKNESS, WALL_COLOR);
+        }
+        for &position in &objects.capture_points {
+            self.fill_circle(position, CAPTURE_POINT_RADIUS, CAPTURE_POINT_COLOR);
+        }
+        if let Some(position) = objects.ghost_agent {
+            self.fill_rect(
+                position - AGENT_HALF_SIZE,
+                position + AGENT_HALF_SIZE,
+                GHOST_AGENT_COLOR,
+            );
+        }
+        if let Some(position) = objects.agent {
+            self.fill_rect(
+                position - AGENT_HALF_SIZE,
+                position + AGENT_HALF_SIZE,
+                AGENT_COLOR,
+            );
+        }
+        for &position in &objects.flags {
+            self.fill_circle(position, FLAG_RADIUS, FLAG_COLOR);
+        }
+    }
+
+    /// Draws every cell of `grid` in the colors of the grid overlay in the window.
+    pub fn draw_grid(&mut self, grid: &OccupancyGrid) {
+        let colors = encode_grid_to_rgba(grid);
+        let origin = Vec2::from(grid.origin);
+        for (index, color) in colors.chunks_exact(4).enumerate() {
+            let cell = Vec2::new((index % grid.width) as f32, (index / grid.width) as f32);
+            let min = origin + cell * grid.cell_size;
+            self.fill_rect(
+                min,
+                min + grid.cell_size,
+                color.try_into().expect("4 bytes per pixel"),
+            );
+        }
+    }
+
+    /// Draws the shapes a policy queued with `DebugDraw`, except that text labels become dots.
+    pub fn draw_debug(&mut self, shapes: &[DebugShape]) {
+        let line = DEBUG_LINE_PIXELS / self.scale;
+        for shape in shapes {
+            match shape {
+                DebugShape::Line { start, end, color } => {
+                    self.draw_segment(*start, *end, line, color.to_u8_array());
+                }
+                DebugShape::Point {
+                    position,
+                    size,
+                    color,
+                } => self.fill_circle(*position, size * 0.5, color.to_u8_array()),
+                DebugShape::Circle {
+                    center,
+                    radius,
+                    color,
+                } => {
+                    let (center, radius, half) = (*center, *radius, line * 0.5);
+                    self.fill_where(
+                        center - radius - half,
+                        center + radius + half,
+                        color.to_u8_array(),
+                        |point| (point.distance(center) - radius).abs() <= half,
+                    );
+                }
+                DebugShape::Text {
+                    position, color, ..
+                } => self.fill_circle(
+                    *position,
+                    DEBUG_LABEL_PIXELS / self.scale,
+                    color.to_u8_array(),
+                ),
+                DebugShape::Heatmap {
+                    origin,
+                    cell_size,
+                    width,
+                    pixels,
+                    ..
+                } => {
+                    for (index, color) in pixels.chunks_exact(4).enumerate() {
+                        let cell = Vec2::new((index % width) as f32, (index / width) as f32);
+                        let min = *origin + cell * *cell_size;
+                        self.fill_rect(
+                            min,
+                            min + *cell_size,
+                            color.try_into().expect("4 bytes per pixel"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn fill_rect(&mut self, min: Vec2, max: Vec2, color: [u8; 4]) {
+        self.fill_where(min, max, color, |_| true);
+    }
+
+    /// Fills a disc, at least a pixel across so that small objects stay visible.
+    pub fn fill_circle(&mut self, center: Vec2, radius: f32, color: [u8; 4]) {
+        let radius = radius.max(0.5 / self.scale);
+        self.fill_where(center - radius, center + radius, color, |point| {
+            point.distance_squared(center) <= radius * radius
+        });
+    }
+
+    /// Draws a line `thickness` wide, at least a pixel, with square ends.
+    pub fn draw_segment(&mut self, start: Vec2, end: Vec2, thickness: f32, color: [u8; 4]) {
+        let half = thickness.max(1.0 / self.scale) * 0.5;
+        let min = start.min(end) - half;
+        let max = start.max(end) + half;
+        let direction = (end - start).normalize_or_zero();
+        let length = start.distance(end);
+        self.fill_where(min, max, color, |point| {
+            let offset = point - start;
+            let along = offset.dot(direction);
+            let across = offset.perp_dot(direction);
+            along >= -half && along <= length + half && across.abs() <= half
+        });
+    }
+
+    /// Blends `color` over every pixel whose center lies in the world box from `min` to `max`
+    /// and passes `inside`.
+    fn fill_where(&mut self, min: Vec2, max: Vec2, color: [u8; 4], inside: impl Fn(Vec2) -> bool) {
+        let to_pixels = |world: Vec2| (world - self.origin) * self.scale - 0.5;
+        let (min, max) = (to_pixels(min).ceil(), to_pixels(max).ceil());
+        let x_range = (min.x.max(0.0) as usize)..(max.x.max(0.0) as usize).min(self.width);
+        let y_range = (min.y.max(0.0) as usize)..(max.y.max(0.0) as usize).min(self.height);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let center = self.origin + (Vec2::new(x as f32, y as f32) + 0.5) / self.scale;
+                if inside(center) {
+                    self.blend(x, y, color);
+                }
+            }
+        }
+    }
+
+    fn blend(&mut self, x: usize, y: usize, [r, g, b, a]: [u8; 4]) {
+        let offset = (x + y * self.width) * 4;
+        let pixel = &mut self.pixels[offset..offset + 4];
+        let alpha = a as u32;
+        for (channel, value) in pixel.iter_mut().zip([r, g, b]) {
+            *channel = ((value as u32 * alpha + *channel as u32 * (255 - alpha)) / 255) as u8;
+        }
+        pixel[3] = (alpha + pixel[3] as u32 * (255 - alpha) / 255) as u8;
+    }
+}
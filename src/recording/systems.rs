@@ -0,0 +1,184 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, ExtendedColorType, Frame, RgbaImage};
+use pyo3::prelude::*;
+
+use crate::agent::{Agent, GhostAgent};
+use crate::core::MazeConfig;
+use crate::debug_draw::DebugShapes;
+use crate::flag::{CapturePoint, Flag};
+use crate::occupancy_grid::{PlayerGrid, TrueGrid};
+use crate::recording::{Canvas, FrameObjects, GridOverlay, RecordingFormat};
+use crate::scene::WallSegments;
+
+#[derive(Resource)]
+pub struct Recorder {
+    directory: PathBuf,
+    width: usize,
+    height: usize,
+    /// Simulated time at which to draw the next frame.
+    next_frame: f32,
+    frames: u32,
+    gif: Option<GifEncoder<BufWriter<File>>>,
+    /// How long each GIF frame is shown.
+    frame_delay: Delay,
+}
+
+/// How hard the GIF encoder works on each frame's palette, from 1 (best) to 30 (fastest).
+const GIF_SPEED: i32 = 10;
+
+pub fn start_recording(mut commands: Commands, config: Res<MazeConfig>) {
+    let recording = &config.recording;
+    let Some(directory) = recording.output_dir.as_ref().map(PathBuf::from) else {
+        return;
+    };
+    let width = (config.maze_generation.width * recording.pixels_per_unit).ceil() as usize;
+    let height = (config.maze_generation.height * recording.pixels_per_unit).ceil() as usize;
+
+    let gif = match recording.format {
+        RecordingFormat::PngSequence => std::fs::create_dir_all(&directory).map(|_| None),
+        RecordingFormat::Gif => std::fs::create_dir_all(&directory)
+            .and_then(|_| File::create(directory.join("recording.gif")))
+            .and_then(|file| {
+                let mut gif = GifEncoder::new_with_speed(BufWriter::new(file), GIF_SPEED);
+                gif.set_repeat(Repeat::Infinite)
+                    .map_err(std::io::Error::other)?;
+                Ok(Some(gif))
+            }),
+    };
+    let gif = match gif {
+        Ok(gif) => gif,
+        Err(e) => {
+            error!(
+                "Failed to start recording in {}: {}",
+                directory.display(),
+                e
+            );
+            return;
+        }
+    };
+
+    info!(
+        "Recording {}x{} frames to {}",
+        width,
+        height,
+        directory.display()
+    );
+    commands.insert_resource(Recorder {
+        directory,
+        width,
+        height,
+        next_frame: 0.0,
+        frames: 0,
+        gif,
+        frame_delay: Delay::from_saturating_duration(Duration::from_secs_f32(
+            1.0 / recording.fps.max(1.0),
+        )),
+    });
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn record_frame(
+    time: Res<Time>,
+    config: Res<MazeConfig>,
+    recorder: Option<ResMut<Recorder>>,
+    walls: Option<Res<WallSegments>>,
+    player_grid: Res<PlayerGrid>,
+    true_grid: Res<TrueGrid>,
+    debug_shapes: Res<DebugShapes>,
+    flags: Query<&GlobalTransform, With<Flag>>,
+    capture_points: Query<&GlobalTransform, With<CapturePoint>>,
+    agent: Query<&Transform, (With<Agent>, Without<GhostAgent>)>,
+    ghost_agent: Query<&Transform, (With<GhostAgent>, Without<Agent>)>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    if time.elapsed_secs() < recorder.next_frame {
+        return;
+    }
+    recorder.next_frame += 1.0 / config.recording.fps.max(1.0);
+
+    let objects = FrameObjects {
+        flags: flags.iter().map(|t| t.translation().xz()).collect(),
+        capture_points: capture_points
+            .iter()
+            .map(|t| t.translation().xz())
+            .collect(),
+        agent: agent.single().ok().map(|t| t.translation.xz()),
+        ghost_agent: ghost_agent.single().ok().map(|t| t.translation.xz()),
+    };
+    let origin = config.maze_generation.origin();
+    let mut canvas = Canvas::new(
+        recorder.width,
+        recorder.height,
+        origin,
+        config.recording.pixels_per_unit,
+    );
+    let walls = walls.as_ref().map(|w| w.0.as_slice()).unwrap_or_default();
+
+    let grid = match config.recording.grid_overlay {
+        GridOverlay::None => None,
+        GridOverlay::Player => Some(player_grid.0.clone()),
+        GridOverlay::True => Some(true_grid.0.clone()),
+    };
+    match grid {
+        Some(grid) => Python::attach(|py| {
+            let grid = grid.read().unwrap();
+            canvas.draw_scene(walls, Some(&grid.borrow(py)), &objects);
+        }),
+        None => canvas.draw_scene(walls, None, &objects),
+    }
+    canvas.draw_debug(&debug_shapes.shapes);
+
+    let frame = recorder.frames;
+    recorder.frames += 1;
+    let (width, height) = (recorder.width as u32, recorder.height as u32);
+    let delay = recorder.frame_delay;
+    let result = match recorder.gif.as_mut() {
+        Some(gif) => {
+            let image = RgbaImage::from_raw(width, height, canvas.pixels)
+                .expect("The canvas should have the recording's size");
+            gif.encode_frame(Frame::from_parts(image, 0, 0, delay))
+                .map_err(std::io::Error::other)
+        }
+        None => {
+            let path = recorder.directory.join(format!("frame_{:05}.png", frame));
+            image::save_buffer(
+                &path,
+                &canvas.pixels,
+                width,
+                height,
+                ExtendedColorType::Rgba8,
+            )
+            .map_err(std::io::Error::other)
+        }
+    };
+    if let Err(e) = result {
+        error!("Failed to write frame {}, stopping recording: {}", frame, e);
+        recorder.gif = None;
+        recorder.next_frame = f32::INFINITY;
+    }
+}
+
+pub fn finish_recording(mut exit_ev: MessageReader<AppExit>, recorder: Option<ResMut<Recorder>>) {
+    if exit_ev.read().next().is_none() {
+        return;
+    }
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+
+    // Dropping the encoder writes the end of the GIF.
+    recorder.gif = None;
+    info!(
+        "Recorded {} frames to {}",
+        recorder.frames,
+        recorder.directory.display()
+    );
+}
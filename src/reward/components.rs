@@ -0,0 +1,36 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::reward::RewardConfig;
+
+/// Accumulates the shaped reward between policy ticks.
+#[derive(Resource, Debug, Default)]
+pub struct RewardTracker {
+    /// Reward earned since the last state reached the policy, excluding the mapping term.
+    pub pending: f32,
+    /// Sum of all rewards handed to the policy so far.
+    pub total: f32,
+    pub captured_flags: u32,
+    pub collisions: u32,
+    pub picked_up_flags: HashSet<Entity>,
+    pub visited_cells: HashSet<(usize, usize)>,
+    pub mapping_accuracy: f32,
+}
+
+impl RewardTracker {
+    /// Returns (step reward, cumulative reward) for the step so far, without closing it.
+    pub fn current_step(&self, mapping_accuracy: f32, config: &RewardConfig) -> (f32, f32) {
+        let reward =
+            self.pending + config.mapping_gain * (mapping_accuracy - self.mapping_accuracy);
+        (reward, self.total + reward)
+    }
+
+    /// Closes the current step once its `reward` has reached the policy. Until then the
+    /// reward keeps accumulating, so dropped states do not lose any of it.
+    pub fn finish_step(&mut self, mapping_accuracy: f32, reward: f32) {
+        self.mapping_accuracy = mapping_accuracy;
+        self.pending = 0.0;
+        self.total += reward;
+    }
+}
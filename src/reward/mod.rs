@@ -0,0 +1,72 @@
+mod components;
+mod systems;
+
+use bevy::prelude::*;
+use derivative::Derivative;
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::gen_stub_pyclass;
+use serde::{Deserialize, Serialize};
+
+pub use components::*;
+
+#[gen_stub_pyclass]
+#[pyclass(name = "RewardConfig")]
+#[derive(Debug, Clone, Resource, Reflect, Derivative, Serialize, Deserialize)]
+#[derivative(Default)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct RewardConfig {
+    /// Reward for each flag delivered to a capture point.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "10.0"))]
+    pub capture_bonus: f32,
+
+    /// Reward for the first time each flag is picked up.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "1.0"))]
+    pub pickup_bonus: f32,
+
+    /// Penalty per second of simulated time.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "0.01"))]
+    pub time_penalty: f32,
+
+    /// Penalty each time the agent runs into a wall.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "0.1"))]
+    pub collision_penalty: f32,
+
+    /// Reward per unit of mapping accuracy (0 to 1) gained against the true grid.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "5.0"))]
+    pub mapping_gain: f32,
+
+    /// Reward for each occupancy grid cell the agent visits for the first time.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "0.01"))]
+    pub exploration_bonus: f32,
+}
+
+#[pymethods]
+impl RewardConfig {
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("RewardConfig({})", self.__str__()?))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to serialize RewardConfig: {}",
+                e
+            ))
+        })
+    }
+}
+
+pub struct RewardPlugin;
+impl Plugin for RewardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RewardTracker>();
+        app.add_systems(Update, systems::accumulate_reward);
+    }
+}
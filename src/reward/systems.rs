@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use pyo3::prelude::*;
+
+use crate::agent::{Agent, CollisionReport};
+use crate::core::MazeConfig;
+use crate::flag::{Flag, FlagCaptureCounts, FlagStatus};
+use crate::occupancy_grid::TrueGrid;
+use crate::reward::RewardTracker;
+
+pub fn accumulate_reward(
+    time: Res<Time>,
+    config: Res<MazeConfig>,
+    scores: Res<FlagCaptureCounts>,
+    true_grid: Res<TrueGrid>,
+    mut tracker: ResMut<RewardTracker>,
+    agent: Query<(&Transform, &CollisionReport), With<Agent>>,
+    flags: Query<(Entity, &Flag), Changed<Flag>>,
+) {
+    let rewards = &config.reward;
+    let Ok((agent_transform, collisions)) = agent.single() else {
+        return;
+    };
+
+    let mut reward = -rewards.time_penalty * time.delta_secs();
+
+    if collisions.total_impacts > tracker.collisions {
+        reward -=
+            rewards.collision_penalty * (collisions.total_impacts - tracker.collisions) as f32;
+        tracker.collisions = collisions.total_impacts;
+    }
+
+    for (entity, flag) in &flags {
+        if flag.status == FlagStatus::PickedUp && tracker.picked_up_flags.insert(entity) {
+            reward += rewards.pickup_bonus;
+        }
+    }
+
+    if scores.0 > tracker.captured_flags {
+        reward += rewards.capture_bonus * (scores.0 - tracker.captured_flags) as f32;
+        tracker.captured_flags = scores.0;
+    }
+
+    let cell = Python::attach(|py| {
+        let grid = true_grid.0.read().unwrap();
+        grid.borrow(py)
+            .world_to_cell((agent_transform.translation.x, agent_transform.translation.z))
+    });
+    if let Some(cell) = cell
+        && tracker.visited_cells.insert(cell)
+    {
+        reward += rewards.exploration_bonus;
+    }
+
+    tracker.pending += reward;
+}
@@ -29,6 +29,15 @@ pub struct EstimatedPositionText;
 #[reflect(Component)]
 pub struct MappingErrorText;
 
+#[derive(Debug, Clone, Copy, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct CoverageText;
+
+/// One bar of the coverage curve in the HUD. Bar 0 is the oldest sample shown.
+#[derive(Debug, Clone, Copy, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct CoverageBar(pub usize);
+
 #[derive(Debug, Clone, Bundle, Default)]
 pub struct WallBundle {
     pub wall: Wall,
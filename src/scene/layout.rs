@@ -0,0 +1,60 @@
+//! Hand-made mazes: the walls, flags, capture points and agent spawn of a scene, saved as YAML by
+//! the editor and loaded through `MazeGenerationConfig.layout` instead of generating a maze.
+
+use anyhow::Context;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::MazeConfig;
+
+/// A scene layout in world (x, y) coordinates, centered on the origin.
+#[derive(Debug, Clone, Default, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MazeLayout {
+    pub width: f32,
+    pub height: f32,
+    /// Wall center lines, `WALL_THICKNESS` wide.
+    pub walls: Vec<((f32, f32), (f32, f32))>,
+    pub flags: Vec<(f32, f32)>,
+    pub capture_points: Vec<(f32, f32)>,
+    /// Where the agent starts. A random free cell when None.
+    pub agent_spawn: Option<(f32, f32)>,
+}
+
+impl MazeLayout {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read maze layout {}", path))?;
+        serde_yaml::from_str(&text).with_context(|| format!("Failed to parse maze layout {}", path))
+    }
+
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        let text = serde_yaml::to_string(self)?;
+        std::fs::write(path, text).with_context(|| format!("Failed to write maze layout {}", path))
+    }
+
+    pub fn wall_segments(&self) -> Vec<(Vec2, Vec2)> {
+        self.walls
+            .iter()
+            .map(|&(start, end)| (start.into(), end.into()))
+            .collect()
+    }
+}
+
+/// Loads the layout named in `maze_generation.layout`, if any, and sizes the maze and its flag
+/// and capture point counts to match it.
+pub fn load_layout(config: &mut MazeConfig) -> anyhow::Result<Option<MazeLayout>> {
+    let Some(path) = &config.maze_generation.layout else {
+        return Ok(None);
+    };
+    let layout = MazeLayout::load(path)?;
+    if layout.width <= 0.0 || layout.height <= 0.0 {
+        anyhow::bail!("Maze layout {} needs a positive width and height", path);
+    }
+
+    config.maze_generation.width = layout.width;
+    config.maze_generation.height = layout.height;
+    config.flags.number = layout.flags.len();
+    config.capture_points.number = layout.capture_points.len();
+    Ok(Some(layout))
+}
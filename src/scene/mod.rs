@@ -1,4 +1,5 @@
 mod components;
+mod layout;
 mod systems;
 mod visual;
 
@@ -9,6 +10,7 @@ use pyo3_stub_gen::derive::gen_stub_pyclass;
 use serde::{Deserialize, Serialize};
 
 pub use components::*;
+pub use layout::*;
 pub use visual::*;
 
 use crate::core::{MazeConfig, StartupSets};
@@ -35,6 +37,27 @@ pub struct MazeGenerationConfig {
     #[pyo3(get, set)]
     #[derivative(Default(value = "5.0"))]
     pub cell_size: f32,
+    /// A YAML layout file saved from the editor, used instead of a generated maze. Its size
+    /// replaces `width` and `height`, and its flags and capture points replace the configured
+    /// numbers.
+    #[pyo3(get, set)]
+    pub layout: Option<String>,
+}
+
+impl MazeGenerationConfig {
+    /// The number of maze cells along x and y.
+    pub fn cells(&self) -> UVec2 {
+        UVec2::new(
+            (self.width / self.cell_size).round() as u32,
+            (self.height / self.cell_size).round() as u32,
+        )
+    }
+
+    /// The world (x, y) coordinates of the maze corner with the lowest x and y. The maze, and
+    /// the occupancy grids covering it, are centered on the world origin.
+    pub fn origin(&self) -> Vec2 {
+        Vec2::new(self.width, self.height) * -0.5
+    }
 }
 
 pub struct ScenePlugin;
@@ -57,6 +80,8 @@ impl Plugin for ScenePlugin {
                 systems::update_time,
                 systems::update_true_position,
                 systems::update_mapping_error,
+                systems::update_coverage,
+                systems::respawn_walls,
             ),
         );
     }
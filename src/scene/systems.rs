@@ -7,22 +7,31 @@ use pyo3::prelude::*;
 use crate::{
     agent::{Agent, COLLISION_LAYER_AGENT},
     core::MazeConfig,
-    occupancy_grid::{LOGIT_CLAMP, PlayerGrid, TrueGrid},
+    occupancy_grid::{ExplorationTracker, LOGIT_CLAMP, MappingError, PlayerGrid, TrueGrid},
     python::game_state::EntityType,
     scene::{
-        COLLISION_LAYER_WALL, EstimatedPositionText, MappingErrorText, TimeText, TruePositionText,
-        WALL_HEIGHT, WALL_THICKNESS, WallBundle, WallGraphicsAssets, WallSegments,
+        COLLISION_LAYER_WALL, CoverageBar, CoverageText, EstimatedPositionText, MappingErrorText,
+        MazeLayout, TimeText, TruePositionText, WALL_HEIGHT, WALL_THICKNESS, Wall, WallBundle,
+        WallGraphicsAssets, WallSegments,
     },
 };
 
+/// Number of coverage samples drawn in the HUD curve.
+const COVERAGE_BARS: usize = 60;
+
 pub fn setup_scene(
     mut commands: Commands,
+    config: Res<MazeConfig>,
     mut meshes: Option<ResMut<Assets<Mesh>>>,
     mut materials: Option<ResMut<Assets<StandardMaterial>>>,
 ) {
     let mut entity = commands.spawn((
         Name::new("Ground Plane"),
-        Transform::from_xyz(0.0, 0.0, 0.0).with_scale(Vec3::new(100.0, 1.0, 100.0)),
+        Transform::from_xyz(0.0, 0.0, 0.0).with_scale(Vec3::new(
+            config.maze_generation.width,
+            1.0,
+            config.maze_generation.height,
+        )),
         RigidBody::Static,
         Collider::cuboid(1.0, 1.0, 1.0),
         CollisionLayers::new(
@@ -82,8 +91,7 @@ fn push_vertical(
 pub fn segments_from_maze(maze: &Maze, config: &MazeConfig, pad: f32) -> Vec<(Vec2, Vec2)> {
     let cell = config.maze_generation.cell_size;
     let (w, h) = maze.size;
-    let x0 = -(w as f32) * cell * 0.5;
-    let z0 = -(h as f32) * cell * 0.5;
+    let Vec2 { x: x0, y: z0 } = config.maze_generation.origin();
 
     // outer bounds for clamping
     let xmin = x0;
@@ -117,38 +125,6 @@ pub fn segments_from_maze(maze: &Maze, config: &MazeConfig, pad: f32) -> Vec<(Ve
     segments
 }
 
-fn overlapping_indexes(
-    aabb_min: Vec2,    // bottom-left  (x,y)
-    aabb_max: Vec2,    // top-right    (x,y)
-    cell_size: f32,    // occupancy grid cell size (world units)
-    world_width: f32,  // world extent in X
-    world_height: f32, // world extent in Y (your Z)
-) -> Vec<(u32, u32)> {
-    let grid_w = (world_width / cell_size).round() as i32;
-    let grid_h = (world_height / cell_size).round() as i32;
-
-    let half_w = world_width * 0.5;
-    let half_h = world_height * 0.5;
-
-    let min_c = (((aabb_min.x + half_w) / cell_size).floor() as i32).clamp(0, grid_w - 1);
-    let min_r = (((aabb_min.y + half_h) / cell_size).floor() as i32).clamp(0, grid_h - 1);
-
-    let max_c = ((((aabb_max.x + half_w) / cell_size).ceil() as i32) - 1).clamp(0, grid_w - 1);
-    let max_r = ((((aabb_max.y + half_h) / cell_size).ceil() as i32) - 1).clamp(0, grid_h - 1);
-
-    if max_c < min_c || max_r < min_r {
-        return Vec::new();
-    }
-
-    let mut out = Vec::with_capacity(((max_c - min_c + 1) * (max_r - min_r + 1)) as usize);
-    for r in min_r..=max_r {
-        for c in min_c..=max_c {
-            out.push((c as u32, r as u32));
-        }
-    }
-    out
-}
-
 pub fn spawn_seed_and_time(
     mut commands: Commands,
     mut config: ResMut<MazeConfig>,
@@ -231,6 +207,38 @@ pub fn spawn_seed_and_time(
                 TextLayout::new_with_justify(Justify::Right),
                 MappingErrorText,
             ));
+
+            parent.spawn((
+                Text::new("Explored:"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(Justify::Right),
+                CoverageText,
+            ));
+
+            parent
+                .spawn(Node {
+                    width: Val::Px(COVERAGE_BARS as f32 * 3.0),
+                    height: Val::Px(30.0),
+                    align_items: AlignItems::End,
+                    column_gap: Val::Px(1.0),
+                    ..default()
+                })
+                .with_children(|curve| {
+                    for i in 0..COVERAGE_BARS {
+                        curve.spawn((
+                            Node {
+                                width: Val::Px(2.0),
+                                height: Val::Percent(0.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgb(0.3, 0.8, 0.4)),
+                            CoverageBar(i),
+                        ));
+                    }
+                });
         });
 }
 
@@ -240,37 +248,55 @@ pub fn update_time(mut query: Query<&mut Text, With<TimeText>>, time: Res<Time>)
     }
 }
 
+/// Counts the mapping errors for the HUD and the dashboard.
 pub fn update_mapping_error(
     player_grid: Res<PlayerGrid>,
     true_grid: Res<TrueGrid>,
+    mut mapping_error: ResMut<MappingError>,
     mut query: Query<&mut Text, With<MappingErrorText>>,
 ) {
-    Python::attach(|py| {
+    let (errors, total) = Python::attach(|py| {
         let player_grid = player_grid.0.read().unwrap();
         let true_grid = true_grid.0.read().unwrap();
-        let player_grid = player_grid.borrow(py);
-        let true_grid = true_grid.borrow(py);
-
-        let mut error = 0;
-        let mut total = 0;
-        for (player_entry, true_entry) in player_grid.grid.iter().zip(true_grid.grid.iter()) {
-            if let Some(true_entity_type) = true_entry.assignment
-                && true_entity_type != EntityType::Flag
-                && true_entity_type != EntityType::CapturePoint
-            {
-                total += 1;
-                if player_entry.assignment != true_entry.assignment {
-                    error += 1;
-                }
-            }
-        }
+        player_grid.borrow(py).mapping_errors(&true_grid.borrow(py))
+    });
+    *mapping_error = MappingError { errors, total };
+    let error_rate = mapping_error.fraction() * 100.0;
 
-        let error_rate = (error as f32) / total.max(1) as f32 * 100.0;
+    for mut text in query.iter_mut() {
+        text.0 = format!("Mapping Error: {errors:.0}/{total} [{error_rate:.1}%]");
+    }
+}
 
-        for mut text in query.iter_mut() {
-            text.0 = format!("Mapping Error: {error:.0}/{total} [{error_rate:.1}%]");
-        }
-    })
+pub fn update_coverage(
+    player_grid: Res<PlayerGrid>,
+    tracker: Res<ExplorationTracker>,
+    mut text: Query<&mut Text, With<CoverageText>>,
+    mut bars: Query<(&CoverageBar, &mut Node)>,
+) {
+    let Some(reachable) = &tracker.reachable else {
+        return;
+    };
+
+    let (explored, total) = Python::attach(|py| {
+        let player_grid = player_grid.0.read().unwrap();
+        player_grid.borrow(py).explored_cells(reachable)
+    });
+    let percent = explored as f32 / total.max(1) as f32 * 100.0;
+    for mut text in text.iter_mut() {
+        text.0 = format!("Explored: {explored}/{total} [{percent:.1}%]");
+    }
+
+    // The most recent samples, newest on the right.
+    let samples = &tracker.coverage[tracker.coverage.len().saturating_sub(COVERAGE_BARS)..];
+    let offset = COVERAGE_BARS - samples.len();
+    for (bar, mut node) in bars.iter_mut() {
+        let fraction = bar
+            .0
+            .checked_sub(offset)
+            .map_or(0.0, |index| samples[index].1);
+        node.height = Val::Percent(fraction * 100.0);
+    }
 }
 
 pub fn update_true_position(
@@ -295,25 +321,12 @@ pub fn spawn_walls(
     true_grid: ResMut<TrueGrid>,
     graphics: Option<Res<WallGraphicsAssets>>,
     config: Res<MazeConfig>,
+    layout: Option<Res<MazeLayout>>,
 ) {
-    let seed = config
-        .maze_generation
-        .seed
-        .expect("Should have generated a seed before the map generation");
-    let mut generator = RbGenerator::new({
-        let mut arr = [0u8; 32];
-        arr[..4].copy_from_slice(&seed.to_le_bytes());
-        Some(arr)
-    });
-
-    let maze = generator
-        .generate(
-            (config.maze_generation.width / config.maze_generation.cell_size).round() as i32,
-            (config.maze_generation.height / config.maze_generation.cell_size).round() as i32,
-        )
-        .expect("Maze generation failed");
-
-    let segments = segments_from_maze(&maze, &config, WALL_THICKNESS * 0.5);
+    let segments = match layout {
+        Some(layout) => layout.wall_segments(),
+        None => generate_segments(&config),
+    };
 
     Python::attach(|py| {
         let grid = true_grid.0.write().unwrap();
@@ -331,7 +344,13 @@ pub fn spawn_walls(
 
     commands.insert_resource(WallSegments(segments.clone()));
     for (p0, p1) in segments {
-        let mut entity = commands.spawn(WallBundle::new(p0, p1, WALL_THICKNESS));
+        spawn_wall(
+            &mut commands,
+            meshes.as_deref_mut(),
+            graphics.as_deref(),
+            p0,
+            p1,
+        );
 
         let aabb_bottom_left = Vec2::new(
             p0.x.min(p1.x) - WALL_THICKNESS * 0.5,
@@ -343,32 +362,82 @@ pub fn spawn_walls(
             p0.y.max(p1.y) + WALL_THICKNESS * 0.5,
         );
 
-        let wall_indexes = overlapping_indexes(
-            aabb_bottom_left,
-            aabb_top_right,
-            config.agent.occupancy_grid_cell_size,
-            config.maze_generation.width,
-            config.maze_generation.height,
-        );
-
         Python::attach(|py| {
             let grid = true_grid.0.write().unwrap();
             let mut py_obj = grid.borrow_mut(py);
-            let width = py_obj.width as u32;
-
-            for (ix, iy) in wall_indexes.iter().copied() {
-                py_obj.grid[(ix + iy * width) as usize].assignment = Some(EntityType::Wall);
-                py_obj.grid[(ix + iy * width) as usize].logit_free = -LOGIT_CLAMP;
-                py_obj.grid[(ix + iy * width) as usize].logit_wall = LOGIT_CLAMP;
-                py_obj.grid[(ix + iy * width) as usize].logit_flag = -LOGIT_CLAMP;
-                py_obj.grid[(ix + iy * width) as usize].logit_capture_point = -LOGIT_CLAMP;
+            let width = py_obj.width;
+
+            for (ix, iy) in py_obj.cells_in_aabb(aabb_bottom_left.into(), aabb_top_right.into()) {
+                py_obj.grid[ix + iy * width].assignment = Some(EntityType::Wall);
+                py_obj.grid[ix + iy * width].logit_free = -LOGIT_CLAMP;
+                py_obj.grid[ix + iy * width].logit_wall = LOGIT_CLAMP;
+                py_obj.grid[ix + iy * width].logit_flag = -LOGIT_CLAMP;
+                py_obj.grid[ix + iy * width].logit_capture_point = -LOGIT_CLAMP;
             }
         });
+    }
+}
 
-        if let (Some(meshes), Some(graphics)) = (&mut meshes, &graphics) {
-            let len = p0.distance(p1);
-            let mesh = meshes.add(Cuboid::new(len, WALL_HEIGHT, WALL_THICKNESS));
-            entity.insert((Mesh3d(mesh), MeshMaterial3d(graphics.material.clone())));
-        }
+fn generate_segments(config: &MazeConfig) -> Vec<(Vec2, Vec2)> {
+    let seed = config
+        .maze_generation
+        .seed
+        .expect("Should have generated a seed before the map generation");
+    let mut generator = RbGenerator::new({
+        let mut arr = [0u8; 32];
+        arr[..4].copy_from_slice(&seed.to_le_bytes());
+        Some(arr)
+    });
+
+    let cells = config.maze_generation.cells();
+    let maze = generator
+        .generate(cells.x as i32, cells.y as i32)
+        .expect("Maze generation failed");
+
+    segments_from_maze(&maze, config, WALL_THICKNESS * 0.5)
+}
+
+fn spawn_wall(
+    commands: &mut Commands,
+    meshes: Option<&mut Assets<Mesh>>,
+    graphics: Option<&WallGraphicsAssets>,
+    p0: Vec2,
+    p1: Vec2,
+) {
+    let mut entity = commands.spawn(WallBundle::new(p0, p1, WALL_THICKNESS));
+    if let (Some(meshes), Some(graphics)) = (meshes, graphics) {
+        let len = p0.distance(p1);
+        let mesh = meshes.add(Cuboid::new(len, WALL_HEIGHT, WALL_THICKNESS));
+        entity.insert((Mesh3d(mesh), MeshMaterial3d(graphics.material.clone())));
+    }
+}
+
+/// Rebuilds the wall entities after the editor changes `WallSegments`. `update_true_grid` picks
+/// up the change in the true grid.
+pub fn respawn_walls(
+    mut commands: Commands,
+    mut meshes: Option<ResMut<Assets<Mesh>>>,
+    graphics: Option<Res<WallGraphicsAssets>>,
+    segments: Option<Res<WallSegments>>,
+    walls: Query<Entity, With<Wall>>,
+) {
+    let Some(segments) = segments else {
+        return;
+    };
+    if !segments.is_changed() || segments.is_added() {
+        return;
+    }
+
+    for entity in &walls {
+        commands.entity(entity).despawn();
+    }
+    for &(p0, p1) in &segments.0 {
+        spawn_wall(
+            &mut commands,
+            meshes.as_deref_mut(),
+            graphics.as_deref(),
+            p0,
+            p1,
+        );
     }
 }
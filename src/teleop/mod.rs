@@ -0,0 +1,51 @@
+//! Driving the agent from the keyboard, for the `teleop` built-in policy.
+
+mod systems;
+
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+
+use crate::agent::BuiltinPolicy;
+use crate::core::MazeConfig;
+
+pub const MOVE_UP_KEY: KeyCode = KeyCode::KeyW;
+pub const MOVE_LEFT_KEY: KeyCode = KeyCode::KeyA;
+pub const MOVE_DOWN_KEY: KeyCode = KeyCode::KeyS;
+pub const MOVE_RIGHT_KEY: KeyCode = KeyCode::KeyD;
+pub const PICKUP_KEY: KeyCode = KeyCode::Space;
+pub const DROP_KEY: KeyCode = KeyCode::KeyE;
+
+/// What the keyboard asks of the agent, shared with the teleop policy on the bridge's worker
+/// thread.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TeleopCommand {
+    /// Direction to drive in world (x, y), of length one or zero.
+    pub direction: Vec2,
+    /// Set when the key is pressed and cleared once the policy acts on it, so that presses
+    /// between two policy calls are not lost.
+    pub pickup: bool,
+    pub drop: bool,
+}
+
+#[derive(Resource, Clone, Default)]
+pub struct TeleopInput(pub Arc<Mutex<TeleopCommand>>);
+
+pub struct TeleopPlugin;
+impl Plugin for TeleopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TeleopInput>();
+
+        let config = app.world().resource::<MazeConfig>();
+        if config.agent.builtin_policy != BuiltinPolicy::Teleop {
+            return;
+        }
+        if config.headless {
+            warn!(
+                "The teleop policy needs a window for keyboard input; the agent will stand still"
+            );
+            return;
+        }
+        app.add_systems(Update, systems::read_teleop_keys);
+    }
+}
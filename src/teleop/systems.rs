@@ -0,0 +1,21 @@
+use bevy::prelude::*;
+
+use crate::teleop::{
+    DROP_KEY, MOVE_DOWN_KEY, MOVE_LEFT_KEY, MOVE_RIGHT_KEY, MOVE_UP_KEY, PICKUP_KEY, TeleopInput,
+};
+
+pub fn read_teleop_keys(keys: Res<ButtonInput<KeyCode>>, input: Res<TeleopInput>) {
+    // The camera looks down with world -z, which is game -y, at the top of the screen.
+    let axis = |negative, positive| {
+        keys.pressed(positive) as i32 as f32 - keys.pressed(negative) as i32 as f32
+    };
+    let direction = Vec2::new(
+        axis(MOVE_LEFT_KEY, MOVE_RIGHT_KEY),
+        axis(MOVE_UP_KEY, MOVE_DOWN_KEY),
+    );
+
+    let mut command = input.0.lock().unwrap();
+    command.direction = direction.normalize_or_zero();
+    command.pickup |= keys.just_pressed(PICKUP_KEY);
+    command.drop |= keys.just_pressed(DROP_KEY);
+}